@@ -0,0 +1,183 @@
+use crate::*;
+use std::cell::Cell;
+use std::fmt;
+use std::slice;
+
+impl Access for CellShared {
+    type Owner<T: ?Sized> = ();
+
+    #[inline(always)]
+    unsafe fn _owner<T: ?Sized>(_value: NonNull<T>) {}
+
+    #[inline(always)]
+    unsafe fn _from_owned<T: ?Sized>(_x: OwnedBox<T>) {
+        unreachable!("only Owned refs own their value")
+    }
+
+    #[inline(always)]
+    fn _fork_any<'a, F1, F2, F3, F4, T: ?Sized, R>(
+        x: Ref<'a, T, Self>,
+        _shared: F1,
+        _unique: F2,
+        _owned: F3,
+        cell: F4,
+    ) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+        F3: FnOnce(OwnedBox<T>) -> R,
+        F4: FnOnce(NonNull<T>) -> R,
+    {
+        cell(x.value)
+    }
+}
+
+impl FieldType for CellShared {
+    #[inline(always)]
+    fn _fork_fields<'a, F1, F2, F3, T: ?Sized, R>(
+        x: Ref<'a, T, Self>,
+        _shared: F1,
+        _unique: F2,
+        cell: F3,
+    ) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+        F3: FnOnce(NonNull<T>) -> R,
+    {
+        cell(x.value)
+    }
+}
+
+impl CellShared {
+    /// Creates a new cell Ref from a borrow of a Cell.
+    #[inline(always)]
+    pub fn new<T: ?Sized>(t: &Cell<T>) -> Ref<'_, T, CellShared> {
+        unsafe { Ref::__new_unsafe_cell(t.as_ptr()) }
+    }
+}
+
+impl<'a, T: ?Sized> Ref<'a, T, CellShared> {
+    #[inline(always)]
+    pub fn as_cell(&self) -> &Cell<T> {
+        unsafe { &*(self.value.as_ptr() as *const Cell<T>) }
+    }
+
+    #[inline(always)]
+    pub fn into_cell(self) -> &'a Cell<T> {
+        unsafe { &*(self.value.as_ptr() as *const Cell<T>) }
+    }
+}
+
+impl<'a, T> Ref<'a, T, CellShared> {
+    #[inline(always)]
+    pub fn set(&self, value: T) {
+        self.as_cell().set(value)
+    }
+
+    #[inline(always)]
+    pub fn replace(&self, value: T) -> T {
+        self.as_cell().replace(value)
+    }
+
+    /// Swaps the values of two cell Refs, which may overlap.
+    #[inline(always)]
+    pub fn swap(&self, other: &Self) {
+        self.as_cell().swap(other.as_cell())
+    }
+}
+
+impl<'a, T: Copy> Ref<'a, T, CellShared> {
+    #[inline(always)]
+    pub fn get(&self) -> T {
+        self.as_cell().get()
+    }
+}
+
+impl<'a, T: Default> Ref<'a, T, CellShared> {
+    #[inline(always)]
+    pub fn take(&self) -> T {
+        self.as_cell().take()
+    }
+}
+
+/// An iterator over the elements of a slice of cells, created by iterating over a `Ref<'a, [T], CellShared>`.
+pub type CellIter<'a, T> =
+    std::iter::Map<slice::Iter<'a, Cell<T>>, fn(&'a Cell<T>) -> Ref<'a, T, CellShared>>;
+
+// A cell Ref to a slice is a slice of cells, like `Cell::as_slice_of_cells`.
+// `IndexRef` and `IntoIteratorRef` only support borrows and owned values, so these are implemented separately.
+impl<'a, T> Ref<'a, [T], CellShared> {
+    /// Projects onto an element of the slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[inline(always)]
+    pub fn index_ref(self, i: usize) -> Ref<'a, T, CellShared> {
+        CellShared::new(&self.into_cell().as_slice_of_cells()[i])
+    }
+}
+
+impl<'a, T> IntoIterator for Ref<'a, [T], CellShared> {
+    type Item = Ref<'a, T, CellShared>;
+    type IntoIter = CellIter<'a, T>;
+    #[inline(always)]
+    fn into_iter(self) -> CellIter<'a, T> {
+        self.into_cell()
+            .as_slice_of_cells()
+            .iter()
+            .map(CellShared::new)
+    }
+}
+
+impl<'a, T, const N: usize> Ref<'a, [T; N], CellShared> {
+    #[inline(always)]
+    fn unsize(self) -> Ref<'a, [T], CellShared> {
+        let cell: &'a Cell<[T]> = self.into_cell();
+        CellShared::new(cell)
+    }
+
+    /// Projects onto an element of the array.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[inline(always)]
+    pub fn index_ref(self, i: usize) -> Ref<'a, T, CellShared> {
+        self.unsize().index_ref(i)
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for Ref<'a, [T; N], CellShared> {
+    type Item = Ref<'a, T, CellShared>;
+    type IntoIter = CellIter<'a, T>;
+    #[inline(always)]
+    fn into_iter(self) -> CellIter<'a, T> {
+        self.unsize().into_iter()
+    }
+}
+
+impl<'a, T: ?Sized> Ref<'a, T, Unique> {
+    /// Converts a unique Ref into a cell Ref, like `Cell::from_mut`.
+    #[inline(always)]
+    pub fn into_cell_shared(self) -> Ref<'a, T, CellShared> {
+        unsafe { Ref::__new_unsafe_cell(self.value.as_ptr()) }
+    }
+}
+
+impl<'a, T: ?Sized> Clone for Ref<'a, T, CellShared> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T: ?Sized> Copy for Ref<'a, T, CellShared> {}
+
+impl<'a, T: Copy + fmt::Debug> fmt::Debug for Ref<'a, T, CellShared> {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_cell().fmt(f)
+    }
+}
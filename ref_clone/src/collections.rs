@@ -0,0 +1,184 @@
+use crate::*;
+use std::collections::linked_list;
+use std::collections::vec_deque;
+use std::collections::LinkedList;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::ops::RangeFrom;
+use std::ops::RangeFull;
+use std::ops::RangeInclusive;
+use std::ops::RangeTo;
+use std::ops::RangeToInclusive;
+
+pub type VecDequeIter<'a, T, S> =
+    RefIter<'a, T, S, vec_deque::Iter<'a, T>, vec_deque::IterMut<'a, T>>;
+pub type LinkedListIter<'a, T, S> =
+    RefIter<'a, T, S, linked_list::Iter<'a, T>, linked_list::IterMut<'a, T>>;
+
+impl<T> IndexRef<usize> for Vec<T> {
+    type Output = T;
+    #[inline(always)]
+    fn index_ref<'a, S: AccessType>(this: Ref<'a, Self, S>, i: usize) -> Ref<'a, T, S> {
+        unsafe {
+            S::_fork_all(
+                this,
+                |x| Ref::new_unchecked(&x[i]),
+                |x| Ref::new_unchecked_mut(&mut x[i]),
+                |x| Ref::new_unchecked_owned(OwnedBox::from_vec_element(x.into_inner(), i)),
+            )
+        }
+    }
+}
+
+impl<'a, T: 'a> IntoIteratorRef<'a> for Vec<T> {
+    type Item = T;
+    type IntoIter<S: AccessType> = RefIter<'a, T, S>;
+    #[inline(always)]
+    fn into_iter_ref<S: AccessType>(this: Ref<'a, Self, S>) -> Self::IntoIter<S> {
+        RefIter::new(
+            this,
+            |x| <[T]>::iter(x),
+            |x| <[T]>::iter_mut(x),
+            |x| OwnedIter::new(x.into_inner()),
+        )
+    }
+}
+
+impl<T> IndexRef<usize> for Box<[T]> {
+    type Output = T;
+    #[inline(always)]
+    fn index_ref<'a, S: AccessType>(this: Ref<'a, Self, S>, i: usize) -> Ref<'a, T, S> {
+        unsafe {
+            S::_fork_all(
+                this,
+                |x| Ref::new_unchecked(&x[i]),
+                |x| Ref::new_unchecked_mut(&mut x[i]),
+                |x| {
+                    Ref::new_unchecked_owned(OwnedBox::from_vec_element(
+                        x.into_inner().into_vec(),
+                        i,
+                    ))
+                },
+            )
+        }
+    }
+}
+
+impl<'a, T: 'a> IntoIteratorRef<'a> for Box<[T]> {
+    type Item = T;
+    type IntoIter<S: AccessType> = RefIter<'a, T, S>;
+    #[inline(always)]
+    fn into_iter_ref<S: AccessType>(this: Ref<'a, Self, S>) -> Self::IntoIter<S> {
+        RefIter::new(
+            this,
+            |x| <[T]>::iter(x),
+            |x| <[T]>::iter_mut(x),
+            |x| OwnedIter::new(x.into_inner().into_vec()),
+        )
+    }
+}
+
+impl<T> IndexRef<usize> for VecDeque<T> {
+    type Output = T;
+    #[inline(always)]
+    fn index_ref<'a, S: AccessType>(this: Ref<'a, Self, S>, i: usize) -> Ref<'a, T, S> {
+        unsafe {
+            S::_fork_all(
+                this,
+                |x| Ref::new_unchecked(&x[i]),
+                |x| Ref::new_unchecked_mut(&mut x[i]),
+                |x| {
+                    Ref::new_unchecked_owned(OwnedBox::from_vec_element(
+                        Vec::from(x.into_inner()),
+                        i,
+                    ))
+                },
+            )
+        }
+    }
+}
+
+impl<'a, T: 'a> IntoIteratorRef<'a> for VecDeque<T> {
+    type Item = T;
+    type IntoIter<S: AccessType> = VecDequeIter<'a, T, S>;
+    #[inline(always)]
+    fn into_iter_ref<S: AccessType>(this: Ref<'a, Self, S>) -> Self::IntoIter<S> {
+        RefIter::new(
+            this,
+            |x| x.iter(),
+            |x| x.iter_mut(),
+            |x| OwnedIter::new(Vec::from(x.into_inner())),
+        )
+    }
+}
+
+impl<'a, T: 'a> IntoIteratorRef<'a> for LinkedList<T> {
+    type Item = T;
+    type IntoIter<S: AccessType> = LinkedListIter<'a, T, S>;
+    #[inline(always)]
+    fn into_iter_ref<S: AccessType>(this: Ref<'a, Self, S>) -> Self::IntoIter<S> {
+        RefIter::new(
+            this,
+            |x| x.iter(),
+            |x| x.iter_mut(),
+            |x| OwnedIter::new(x.into_inner().into_iter().collect()),
+        )
+    }
+}
+
+// A string may only be indexed by ranges, as a unique borrow of a single byte could make it invalid UTF-8.
+// Owned strings are sliced by copying the range into a new `Box<str>`.
+macro_rules! index_str {
+    ($($range:ty),*) => {
+        $(
+            impl IndexRef<$range> for str {
+                type Output = str;
+                #[inline(always)]
+                fn index_ref<'a, S: AccessType>(this: Ref<'a, Self, S>, index: $range) -> Ref<'a, str, S> {
+                    unsafe {
+                        S::_fork_all(
+                            this,
+                            |x| Ref::new_unchecked(&x[index.clone()]),
+                            |x| Ref::new_unchecked_mut(&mut x[index.clone()]),
+                            |x| Ref::new_unchecked_box(x[index.clone()].into()),
+                        )
+                    }
+                }
+            }
+
+            impl IndexRef<$range> for String {
+                type Output = str;
+                #[inline(always)]
+                fn index_ref<'a, S: AccessType>(this: Ref<'a, Self, S>, index: $range) -> Ref<'a, str, S> {
+                    unsafe {
+                        S::_fork_all(
+                            this,
+                            |x| Ref::new_unchecked(&x[index.clone()]),
+                            |x| Ref::new_unchecked_mut(&mut x[index.clone()]),
+                            |x| Ref::new_unchecked_box(x[index.clone()].into()),
+                        )
+                    }
+                }
+            }
+        )*
+    };
+}
+
+index_str!(
+    Range<usize>,
+    RangeFrom<usize>,
+    RangeFull,
+    RangeInclusive<usize>,
+    RangeTo<usize>,
+    RangeToInclusive<usize>
+);
+
+// Strings have no `IntoIteratorRef`, as their chars are decoded rather than stored, so there is nothing for a Ref to point to.
+// Their bytes are stored, but may only be borrowed through a Shared ref for the same reason as indexing.
+impl<'a> Ref<'a, str, Shared> {
+    /// Views the string as its UTF-8 bytes, which may then be indexed and iterated as Refs.
+    #[inline(always)]
+    pub fn as_bytes(this: Self) -> Ref<'a, [u8], Shared> {
+        Ref::new(this.into_ref().as_bytes())
+    }
+}
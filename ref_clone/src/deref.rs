@@ -0,0 +1,87 @@
+use crate::*;
+use std::borrow::BorrowMut;
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+impl<T> DerefRef for Vec<T> {
+    type Target = [T];
+    #[inline(always)]
+    fn deref_ref<'a, S: RefType>(this: Ref<'a, Self, S>) -> Ref<'a, [T], S> {
+        Ref::map(this, |x| x.as_slice(), |x| x.as_mut_slice())
+    }
+}
+
+impl DerefRef for String {
+    type Target = str;
+    #[inline(always)]
+    fn deref_ref<'a, S: RefType>(this: Ref<'a, Self, S>) -> Ref<'a, str, S> {
+        Ref::map(this, |x| x.as_str(), |x| x.as_mut_str())
+    }
+}
+
+impl DerefRef for PathBuf {
+    type Target = Path;
+    #[inline(always)]
+    fn deref_ref<'a, S: RefType>(this: Ref<'a, Self, S>) -> Ref<'a, Path, S> {
+        Ref::map(this, |x| x.as_path(), |x| &mut **x)
+    }
+}
+
+impl DerefRef for OsString {
+    type Target = OsStr;
+    #[inline(always)]
+    fn deref_ref<'a, S: RefType>(this: Ref<'a, Self, S>) -> Ref<'a, OsStr, S> {
+        Ref::map(this, |x| x.as_os_str(), |x| &mut **x)
+    }
+}
+
+/// Unique refs clone the value if it is borrowed, like `Cow::to_mut`.
+impl<B: ?Sized + ToOwned> DerefRef for Cow<'_, B>
+where
+    B::Owned: BorrowMut<B>,
+{
+    type Target = B;
+    #[inline(always)]
+    fn deref_ref<'a, S: RefType>(this: Ref<'a, Self, S>) -> Ref<'a, B, S> {
+        Ref::map(this, |x| &**x, |x| x.to_mut().borrow_mut())
+    }
+}
+
+/// Unique refs use `Rc::get_mut`, and panic if the Rc is shared. Use `try_deref_ref` to handle this.
+impl<T: ?Sized> DerefRef for Rc<T> {
+    type Target = T;
+    #[inline(always)]
+    fn deref_ref<'a, S: RefType>(this: Ref<'a, Self, S>) -> Ref<'a, T, S> {
+        this.try_deref_ref().ok().expect("Rc is shared")
+    }
+}
+
+impl<'a, T: ?Sized, S: RefType> Ref<'a, Rc<T>, S> {
+    /// Dereferences the Rc, giving back the Ref if it is Unique and the Rc is shared.
+    #[inline(always)]
+    pub fn try_deref_ref(self) -> Result<Ref<'a, T, S>, Self> {
+        Ref::filter_map(self, |x| Some(&**x), |x| Rc::get_mut(x))
+    }
+}
+
+/// Unique refs use `Arc::get_mut`, and panic if the Arc is shared. Use `try_deref_ref` to handle this.
+impl<T: ?Sized> DerefRef for Arc<T> {
+    type Target = T;
+    #[inline(always)]
+    fn deref_ref<'a, S: RefType>(this: Ref<'a, Self, S>) -> Ref<'a, T, S> {
+        this.try_deref_ref().ok().expect("Arc is shared")
+    }
+}
+
+impl<'a, T: ?Sized, S: RefType> Ref<'a, Arc<T>, S> {
+    /// Dereferences the Arc, giving back the Ref if it is Unique and the Arc is shared.
+    #[inline(always)]
+    pub fn try_deref_ref(self) -> Result<Ref<'a, T, S>, Self> {
+        Ref::filter_map(self, |x| Some(&**x), |x| Arc::get_mut(x))
+    }
+}
@@ -0,0 +1,42 @@
+use crate::*;
+
+/// A boxed RefFn, which can be named, stored in tables and applied any number of times.
+///
+/// This is created with `DynRefFn::new` or `RefFn::boxed`, and may be composed at runtime using `then`.
+pub struct DynRefFn<A: ?Sized, B: ?Sized> {
+    apply: Box<dyn Fn(&A) -> &B>,
+    apply_mut: Box<dyn Fn(&mut A) -> &mut B>,
+}
+
+impl<A: ?Sized, B: ?Sized> DynRefFn<A, B> {
+    #[inline(always)]
+    pub fn new(
+        apply: impl Fn(&A) -> &B + 'static,
+        apply_mut: impl Fn(&mut A) -> &mut B + 'static,
+    ) -> Self {
+        DynRefFn {
+            apply: Box::new(apply),
+            apply_mut: Box::new(apply_mut),
+        }
+    }
+
+    #[inline(always)]
+    pub fn ap<'a, T: RefType>(&self, x: Ref<'a, A, T>) -> Ref<'a, B, T>
+    where
+        B: 'a,
+    {
+        T::_apply_once(RefFn::new(&*self.apply, &*self.apply_mut), x)
+    }
+
+    /// Composes two DynRefFns, applying `self` and then `other`.
+    #[inline(always)]
+    pub fn then<C: ?Sized + 'static>(self, other: DynRefFn<B, C>) -> DynRefFn<A, C>
+    where
+        A: 'static,
+        B: 'static,
+    {
+        let (apply, apply_mut) = (self.apply, self.apply_mut);
+        let (then, then_mut) = (other.apply, other.apply_mut);
+        DynRefFn::new(move |x| then(apply(x)), move |x| then_mut(apply_mut(x)))
+    }
+}
@@ -0,0 +1,173 @@
+use crate::*;
+use std::cell;
+use std::fmt;
+use std::sync::MutexGuard;
+use std::sync::RwLockReadGuard;
+use std::sync::RwLockWriteGuard;
+
+/// A guard whose value may be borrowed with the borrow type `S` for as long as the guard is alive.
+///
+/// # Safety
+///
+/// The pointer returned by `value_ptr` must stay valid for as long as the guard is alive, even if the guard is moved.
+/// If `S` is `Unique`, the guard must have exclusive access to the value, and the pointer must be derived from a unique borrow.
+pub unsafe trait StableGuard<S: RefType>: Deref {
+    fn value_ptr(&mut self) -> NonNull<Self::Target>;
+}
+
+/// A Ref which owns the guard it borrows from, such as a `cell::Ref` or a `MutexGuard`.
+///
+/// This is like `std::cell::Ref`, but generic over the type of the borrow. It derefs to the value itself,
+/// and `reborrow` gives a Ref which borrows from the GuardRef. Use `map` to project the value while keeping the guard.
+pub struct GuardRef<'a, G, T: ?Sized, S: RefType> {
+    value: Ref<'a, T, S>,
+    guard: G,
+}
+
+impl<'a, G: StableGuard<S> + 'a, S: RefType> GuardRef<'a, G, G::Target, S> {
+    #[inline(always)]
+    pub fn new(mut guard: G) -> Self {
+        let value = guard.value_ptr();
+        unsafe { GuardRef::from_raw_parts(value, guard) }
+    }
+}
+
+impl<'a, G: StableGuard<Shared> + 'a, S: RefType> GuardRef<'a, G, G::Target, S> {
+    /// Creates a GuardRef of any type from a guard.
+    ///
+    /// # Safety
+    ///
+    /// If `S` is `Unique`, `guard` must satisfy the requirements of `StableGuard<Unique>`.
+    #[inline(always)]
+    pub(crate) unsafe fn new_unchecked(mut guard: G) -> Self {
+        let value = guard.value_ptr();
+        GuardRef::from_raw_parts(value, guard)
+    }
+}
+
+impl<'a, G, T: ?Sized, S: RefType> GuardRef<'a, G, T, S> {
+    /// # Safety
+    ///
+    /// `value` must be borrowed from `guard` as described by `StableGuard<S>`.
+    #[inline(always)]
+    unsafe fn from_raw_parts(value: NonNull<T>, guard: G) -> Self {
+        GuardRef {
+            value: Ref {
+                value,
+                owner: S::_owner(value),
+                ty: PhantomData,
+            },
+            guard,
+        }
+    }
+
+    /// Applies a RefFn to the value, keeping the guard.
+    #[inline(always)]
+    pub fn map<F1, F2, U: ?Sized + 'a>(self, f: RefFn<F1, F2, T, U>) -> GuardRef<'a, G, U, S>
+    where
+        F1: FnOnce(&T) -> &U,
+        F2: FnOnce(&mut T) -> &mut U,
+    {
+        GuardRef {
+            value: f.ap(self.value),
+            guard: self.guard,
+        }
+    }
+
+    /// Reborrows the value as a Ref for as long as the GuardRef is borrowed.
+    #[inline(always)]
+    pub fn reborrow(&mut self) -> Ref<'_, T, S> {
+        self.value.reborrow()
+    }
+
+    /// Borrows the value as a shared Ref.
+    #[inline(always)]
+    pub fn as_shared(&self) -> Ref<'_, T, Shared> {
+        self.value.as_shared()
+    }
+
+    /// Releases the projection, returning the guard.
+    #[inline(always)]
+    pub fn into_guard(self) -> G {
+        self.guard
+    }
+}
+
+impl<'a, G, T: ?Sized, S: RefType> Deref for GuardRef<'a, G, T, S> {
+    type Target = T;
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<'a, G, T: ?Sized> DerefMut for GuardRef<'a, G, T, Unique> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<'a, 'b, G, T: ?Sized, S: RefType, Wrapped> RefAccessors<Wrapped>
+    for &'b mut GuardRef<'a, G, T, S>
+where
+    Ref<'b, T, S>: RefAccessors<Wrapped>,
+{
+    #[inline(always)]
+    fn to_wrapped(self) -> Wrapped {
+        self.reborrow().to_wrapped()
+    }
+}
+
+impl<'a, 'b, G, T: ?Sized, S: RefType, Wrapped> RefAccessorsMut<'b, Wrapped>
+    for GuardRef<'a, G, T, S>
+where
+    T: 'b,
+    Ref<'b, T, S>: RefAccessors<Wrapped>,
+{
+    #[inline(always)]
+    fn wrap(&'b mut self) -> Wrapped {
+        self.reborrow().to_wrapped()
+    }
+}
+
+impl<'a, G, T: ?Sized + fmt::Debug, S: RefType> fmt::Debug for GuardRef<'a, G, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+unsafe impl<'a, T: ?Sized> StableGuard<Shared> for cell::Ref<'a, T> {
+    #[inline(always)]
+    fn value_ptr(&mut self) -> NonNull<T> {
+        NonNull::from(&**self)
+    }
+}
+
+unsafe impl<'a, T: ?Sized, S: RefType> StableGuard<S> for cell::RefMut<'a, T> {
+    #[inline(always)]
+    fn value_ptr(&mut self) -> NonNull<T> {
+        NonNull::from(&mut **self)
+    }
+}
+
+unsafe impl<'a, T: ?Sized, S: RefType> StableGuard<S> for MutexGuard<'a, T> {
+    #[inline(always)]
+    fn value_ptr(&mut self) -> NonNull<T> {
+        NonNull::from(&mut **self)
+    }
+}
+
+unsafe impl<'a, T: ?Sized> StableGuard<Shared> for RwLockReadGuard<'a, T> {
+    #[inline(always)]
+    fn value_ptr(&mut self) -> NonNull<T> {
+        NonNull::from(&**self)
+    }
+}
+
+unsafe impl<'a, T: ?Sized, S: RefType> StableGuard<S> for RwLockWriteGuard<'a, T> {
+    #[inline(always)]
+    fn value_ptr(&mut self) -> NonNull<T> {
+        NonNull::from(&mut **self)
+    }
+}
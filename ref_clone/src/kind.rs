@@ -0,0 +1,58 @@
+use crate::*;
+
+/// The type of a borrow as a value, given by `RefType::KIND`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RefKind {
+    Shared,
+    Unique,
+}
+
+/// Either a shared or a unique borrow, as returned by `Ref::into_either`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+impl<'a, T: ?Sized, S: RefType> Ref<'a, T, S> {
+    /// Converts the Ref into the underlying borrow, which is `Left` if it is Shared and `Right` if it is Unique.
+    #[inline(always)]
+    pub fn into_either(self) -> Either<&'a T, &'a mut T> {
+        S::_fork(self, Either::Left, Either::Right)
+    }
+
+    /// Converts the Ref into a Unique Ref if it is one, or into a Shared Ref otherwise.
+    ///
+    /// This allows code generic over the type of the borrow to attempt an operation which requires a unique borrow.
+    #[inline(always)]
+    pub fn try_into_unique(self) -> Result<Ref<'a, T, Unique>, Ref<'a, T, Shared>> {
+        S::_fork(self, |x| Err(Shared::new(x)), |x| Ok(Unique::new(x)))
+    }
+}
+
+impl<'a, T: ?Sized, S: RefType> Ref<'a, T, S> {
+    /// Converts the Ref into the underlying borrow, `&'a T` if it is Shared and `&'a mut T` if it is Unique.
+    #[inline(always)]
+    pub fn into_native(self) -> S::Native<'a, T> {
+        S::_into_native(self)
+    }
+
+    /// Converts a borrow back into a Ref. This is the inverse of `into_native`.
+    #[inline(always)]
+    pub fn from_native(x: S::Native<'a, T>) -> Self {
+        S::_from_native(x)
+    }
+
+    /// Calls `shared` or `unique` with the underlying borrow, returning an `S::Select<A, B>`.
+    ///
+    /// This is an associated function so that it does not hide a `select` method of `T`.
+    /// It is used as `Ref::select(x, |x| x.iter(), |x| x.iter_mut())`.
+    #[inline(always)]
+    pub fn select<A, B, F1, F2>(this: Self, shared: F1, unique: F2) -> S::Select<A, B>
+    where
+        F1: FnOnce(&'a T) -> A,
+        F2: FnOnce(&'a mut T) -> B,
+    {
+        S::_select(this, shared, unique)
+    }
+}
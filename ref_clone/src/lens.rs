@@ -0,0 +1,124 @@
+use crate::*;
+use std::fmt;
+
+/// A reusable projection from an `A` to a `B` which may be applied to both Shared and Unique refs.
+///
+/// Unlike a RefFn, a lens is applied by reference, so it may be stored and passed around.
+/// `#[RefAccessors]` generates a lens for every field of a struct, such as `Foo::lens_x()`.
+///
+/// A RefFn is a lens when its functions are `Fn`, such as when it is created from functions rather than closures
+/// written inside `RefFn::new`, and a DynRefFn is always a lens. `to_ref_fn` converts a lens back into a RefFn.
+pub trait RefLens<A: ?Sized, B: ?Sized> {
+    fn get<'a, S: RefType>(&self, x: Ref<'a, A, S>) -> Ref<'a, B, S>;
+
+    /// Composes two lenses, applying `self` and then `other`.
+    ///
+    /// A lens must work for borrows of any lifetime, so unlike `RefFn::then`, the intermediate type `B` must be `'static`.
+    #[inline(always)]
+    fn then<C: ?Sized, L: RefLens<B, C>>(self, other: L) -> LensThen<Self, L, B>
+    where
+        Self: Sized,
+        B: 'static,
+    {
+        LensThen {
+            first: self,
+            second: other,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Borrows the lens as a RefFn, for use with functions which take a RefFn.
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    fn to_ref_fn(
+        &self,
+    ) -> RefFn<impl FnOnce(&A) -> &B + '_, impl FnOnce(&mut A) -> &mut B + '_, A, B> {
+        RefFn::new(
+            move |x| self.get(Shared::new(x)).into_ref(),
+            move |x| self.get(Unique::new(x)).into_mut(),
+        )
+    }
+}
+
+/// The composition of two lenses, created with `RefLens::then`.
+pub struct LensThen<L1, L2, B: ?Sized> {
+    first: L1,
+    second: L2,
+    _marker: PhantomData<fn(&B)>,
+}
+
+impl<A: ?Sized, B: ?Sized + 'static, C: ?Sized, L1, L2> RefLens<A, C> for LensThen<L1, L2, B>
+where
+    L1: RefLens<A, B>,
+    L2: RefLens<B, C>,
+{
+    #[inline(always)]
+    fn get<'a, S: RefType>(&self, x: Ref<'a, A, S>) -> Ref<'a, C, S> {
+        self.second.get(self.first.get(x))
+    }
+}
+
+impl<L1: Clone, L2: Clone, B: ?Sized> Clone for LensThen<L1, L2, B> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        LensThen {
+            first: self.first.clone(),
+            second: self.second.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<L1: Copy, L2: Copy, B: ?Sized> Copy for LensThen<L1, L2, B> {}
+
+/// The lens of the `N`th field of the struct `T`, created with the `lens_` functions generated by `#[RefAccessors]`.
+pub struct FieldLens<T: ?Sized, const N: usize> {
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<T: ?Sized, const N: usize> FieldLens<T, N> {
+    /// # Safety
+    ///
+    /// The lens gives access to the field regardless of its visibility,
+    /// so it may only be created where the field is visible.
+    #[doc(hidden)]
+    #[inline(always)]
+    pub const unsafe fn __new() -> Self {
+        FieldLens {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized, const N: usize> Clone for FieldLens<T, N> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized, const N: usize> Copy for FieldLens<T, N> {}
+
+impl<T: ?Sized, const N: usize> fmt::Debug for FieldLens<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FieldLens<{}, {}>", std::any::type_name::<T>(), N)
+    }
+}
+
+impl<F1, F2, A: ?Sized, B: ?Sized> RefLens<A, B> for RefFn<F1, F2, A, B>
+where
+    F1: Fn(&A) -> &B,
+    F2: Fn(&mut A) -> &mut B,
+{
+    #[inline(always)]
+    fn get<'a, S: RefType>(&self, x: Ref<'a, A, S>) -> Ref<'a, B, S> {
+        S::_apply_once(RefFn::new(&self.apply, &self.apply_mut), x)
+    }
+}
+
+impl<A: ?Sized, B: ?Sized> RefLens<A, B> for DynRefFn<A, B> {
+    #[inline(always)]
+    fn get<'a, S: RefType>(&self, x: Ref<'a, A, S>) -> Ref<'a, B, S> {
+        self.ap(x)
+    }
+}
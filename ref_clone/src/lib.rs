@@ -5,6 +5,7 @@
 //! Example:
 //!
 //! ```
+//! # use ref_clone::*;
 //! #[RefAccessors]
 //! struct Example {
 //!     pub value: u8,
@@ -32,24 +33,65 @@
 //!     }
 //! }
 //! ```
-
-#![allow(incomplete_features)]
-#![feature(const_generics)]
-#![feature(arbitrary_self_types)]
-#![feature(generic_associated_types)]
+//!
+//! The crate builds on stable Rust. Projections such as `index_ref`, `deref_ref` and `into_iter_ref` are
+//! associated functions on their traits, and are also available as methods on `Ref` itself.
+//!
+//! Methods taking `self: Ref<'a, Self, S>` directly need the unstable `arbitrary_self_types` feature, which only applies
+//! to the crate that enables it. Code that wants that receiver syntax must use a nightly compiler and add
+//! `#![feature(arbitrary_self_types)]` to its own crate root.
 
 pub use ref_clone_derive::*;
+use std::borrow::Borrow;
+use std::borrow::BorrowMut;
+use std::cmp::Ordering;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::iter::FusedIterator;
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::ops::DerefMut;
+use std::ptr::NonNull;
 use std::slice::Iter;
+use std::slice::IterMut;
 
 /// The Ref type. Third type parameter is the type of the Borrow.
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[repr(transparent)]
-pub struct Ref<'a, T: ?Sized, S: RefType> {
-    value: &'a T,
-    ty: PhantomData<S>,
+///
+/// The value is stored as a pointer which, for `Unique` refs, is derived from the original `&mut T`.
+/// `Owned` refs own their value, which is kept on the heap and dropped when the Ref is dropped. Values split from a larger
+/// value, such as its fields or the elements of a vector, stay in its allocation, which is freed once they are all dropped.
+/// `Send` and `Sync` match those of `&'a T`, `&'a mut T` and `T` for `Shared`, `Unique` and `Owned` respectively.
+/// As variance cannot depend on the type of the borrow, a `Ref` is covariant in `'a` and invariant in `T` for every type
+/// of borrow, like `&'a mut T`:
+///
+/// ```compile_fail
+/// # use ref_clone::*;
+/// fn shorten<'a>(x: Ref<'a, &'static str, Unique>) -> Ref<'a, &'a str, Unique> {
+///     x
+/// }
+/// ```
+///
+/// This includes `Shared`, unlike `&'a T`:
+///
+/// ```compile_fail
+/// # use ref_clone::*;
+/// fn shorten<'a>(x: Ref<'a, &'static str, Shared>) -> Ref<'a, &'a str, Shared> {
+///     x
+/// }
+/// ```
+///
+/// A Shared Ref may instead be converted through the borrow itself, which is covariant:
+///
+/// ```
+/// # use ref_clone::*;
+/// fn shorten<'a>(x: Ref<'a, &'static str, Shared>) -> Ref<'a, &'a str, Shared> {
+///     Shared::new(x.into_ref())
+/// }
+/// ```
+pub struct Ref<'a, T: ?Sized, S: Access> {
+    value: NonNull<T>,
+    owner: S::Owner<T>,
+    ty: PhantomData<(&'a mut T, S)>,
 }
 
 /// Shared Reference type.
@@ -58,26 +100,196 @@ pub struct Shared;
 /// Unique Reference type.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Unique;
+/// Owned type. An owned Ref holds the value itself rather than a borrow of it.
+///
+/// As nothing is borrowed, the lifetime of an owned Ref constrains nothing, and may be chosen freely. It only exists so
+/// that owned Refs are of the same `Ref` type as borrows, and may be passed to the same generic code.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Owned;
+/// Cell Reference type. A `Ref<'a, T, CellShared>` is a view of a `&'a Cell<T>`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CellShared;
 
 /// A data structure to allow choosing either of `apply` or `apply_mut` to run depending on whether the ref is a Shared or a Unique ref.
-pub struct RefFn<F1, F2, A, B>
-where
-    F1: FnOnce(&A) -> &B,
-    F2: FnOnce(&mut A) -> &mut B,
-{
+///
+/// If an `apply_owned` function is given using `RefFn::new_owned`, the RefFn may also be applied to Owned refs.
+pub struct RefFn<F1, F2, A: ?Sized, B: ?Sized, F3 = Unowned> {
     pub apply: F1,
     pub apply_mut: F2,
-    _marker: PhantomData<*const (A, B)>,
+    pub apply_owned: F3,
+    _marker: PhantomData<(*const A, *const B)>,
+}
+
+/// Used in place of `apply_owned` for a RefFn which can not be applied to Owned refs.
+#[derive(Debug, Clone, Copy)]
+pub struct Unowned;
+
+/// Any type of access that a Ref may have to its value.
+///
+/// This is either an `AccessType` or `CellShared`. Unlike an AccessType, `CellShared` does not allow borrowing the value,
+/// as it may be changed through another Ref at any time.
+pub trait Access: private::Sealed + Copy {
+    /// Keeps the value of an Owned ref alive. This is `()` for borrows.
+    #[doc(hidden)]
+    type Owner<T: ?Sized>;
+
+    /// Creates the owner of a Ref pointing to `value`.
+    ///
+    /// # Safety
+    ///
+    /// For Owned refs, `value` must come from `Box::into_raw`, and is owned by the result.
+    #[doc(hidden)]
+    unsafe fn _owner<T: ?Sized>(value: NonNull<T>) -> Self::Owner<T>;
+
+    /// Creates the owner of an Owned ref from the value it owns.
+    ///
+    /// # Safety
+    ///
+    /// `Self` must be `Owned`.
+    #[doc(hidden)]
+    unsafe fn _from_owned<T: ?Sized>(x: OwnedBox<T>) -> Self::Owner<T>;
+
+    /// Calls `shared`, `unique`, `owned` or `cell` depending on the type of access.
+    /// `cell` is given the pointer to the value, which must not be turned into a reference.
+    ///
+    /// This is used by ref_clone_derive for structs.
+    #[doc(hidden)]
+    fn _fork_any<'a, F1, F2, F3, F4, T: ?Sized, R>(
+        x: Ref<'a, T, Self>,
+        shared: F1,
+        unique: F2,
+        owned: F3,
+        cell: F4,
+    ) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+        F3: FnOnce(OwnedBox<T>) -> R,
+        F4: FnOnce(NonNull<T>) -> R;
+}
+
+/// The type of access that a Ref has to its value.
+///
+/// This may either be a borrow (`Shared` or `Unique`, see `RefType`) or `Owned`.
+pub trait AccessType: Access {
+    fn _apply<F1, F2, F3, A, B>(f: RefFn<F1, F2, A, B, F3>, x: Ref<A, Self>) -> Ref<B, Self>
+    where
+        F1: FnOnce(&A) -> &B,
+        F2: FnOnce(&mut A) -> &mut B,
+        F3: FnOnce(A) -> B;
+
+    /// Calls `shared`, `unique` or `owned` with the underlying value depending on the type of access.
+    ///
+    /// This is used by ref_clone_derive.
+    #[doc(hidden)]
+    fn _fork_all<'a, F1, F2, F3, T: ?Sized, R>(
+        x: Ref<'a, T, Self>,
+        shared: F1,
+        unique: F2,
+        owned: F3,
+    ) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+        F3: FnOnce(OwnedBox<T>) -> R;
+}
+
+/// A type of access which may be projected onto the fields of a struct.
+///
+/// This may either be a `RefType` or `CellShared`.
+pub trait FieldType: Access {
+    /// Calls `shared`, `unique` or `cell` depending on the type of access.
+    /// `cell` is given the pointer to the value, which must not be turned into a reference.
+    ///
+    /// This is used by ref_clone_derive for structs.
+    #[doc(hidden)]
+    fn _fork_fields<'a, F1, F2, F3, T: ?Sized, R>(
+        x: Ref<'a, T, Self>,
+        shared: F1,
+        unique: F2,
+        cell: F3,
+    ) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+        F3: FnOnce(NonNull<T>) -> R;
 }
 
 /// The type of the borrow.
 ///
 /// This may either be Shared or Unique.
-pub trait RefType: private::Sealed + Copy {
-    fn _apply_once<F1, F2, A, B>(f: RefFn<F1, F2, A, B>, x: Ref<A, Self>) -> Ref<B, Self>
+pub trait RefType: AccessType + FieldType {
+    /// The type of the borrow as a value, for code which is generic over it.
+    const KIND: RefKind;
+
+    /// `A` if the borrow is Shared and `B` if it is Unique, such as `Iter` and `IterMut`.
+    type Select<A, B>;
+
+    /// The borrow itself, `&'a T` if it is Shared and `&'a mut T` if it is Unique.
+    type Native<'a, T: ?Sized + 'a>;
+
+    #[doc(hidden)]
+    fn _select<'a, F1, F2, T: ?Sized, A, B>(
+        x: Ref<'a, T, Self>,
+        shared: F1,
+        unique: F2,
+    ) -> Self::Select<A, B>
+    where
+        F1: FnOnce(&'a T) -> A,
+        F2: FnOnce(&'a mut T) -> B;
+
+    #[doc(hidden)]
+    fn _into_native<'a, T: ?Sized>(x: Ref<'a, T, Self>) -> Self::Native<'a, T>;
+
+    #[doc(hidden)]
+    fn _from_native<'a, T: ?Sized>(x: Self::Native<'a, T>) -> Ref<'a, T, Self>;
+
+    fn _apply_once<F1, F2, A: ?Sized, B: ?Sized>(
+        f: RefFn<F1, F2, A, B>,
+        x: Ref<A, Self>,
+    ) -> Ref<B, Self>
     where
         F1: FnOnce(&A) -> &B,
         F2: FnOnce(&mut A) -> &mut B;
+
+    /// Calls `shared` or `unique` with the underlying borrow depending on the type of the borrow.
+    ///
+    /// This is used by ref_clone_derive.
+    #[doc(hidden)]
+    fn _fork<'a, F1, F2, T: ?Sized, R>(x: Ref<'a, T, Self>, shared: F1, unique: F2) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R;
+
+    /// Calls `shared` or `unique` depending on the type of the borrow.
+    #[doc(hidden)]
+    fn _choose<F1, F2, R>(shared: F1, unique: F2) -> R
+    where
+        F1: FnOnce() -> R,
+        F2: FnOnce() -> R;
+}
+
+/// A type of borrow which can be weakened into `S`, such as `Unique` into `Shared`.
+///
+/// Every type of borrow is at least as strong as `Shared`, so a function taking `Ref<'a, T, S>` with `S: Weaker<Shared>`
+/// accepts any Ref and may downgrade it with `downgrade` or `into_shared`.
+pub trait Weaker<S: RefType>: RefType {
+    #[doc(hidden)]
+    fn _weaken<'a, T: ?Sized>(x: Ref<'a, T, Self>) -> Ref<'a, T, S>;
+}
+
+impl<S: RefType> Weaker<Shared> for S {
+    #[inline(always)]
+    fn _weaken<'a, T: ?Sized>(x: Ref<'a, T, Self>) -> Ref<'a, T, Shared> {
+        Shared::new(x.into_ref())
+    }
+}
+
+impl Weaker<Unique> for Unique {
+    #[inline(always)]
+    fn _weaken<'a, T: ?Sized>(x: Ref<'a, T, Self>) -> Ref<'a, T, Unique> {
+        x
+    }
 }
 
 pub trait IntoRef {
@@ -89,7 +301,32 @@ pub trait RefAccessors<Wrapped> {
     fn to_wrapped(self) -> Wrapped;
 }
 
-impl<F1, F2, A, B> RefFn<F1, F2, A, B>
+/// Projects a pinned Ref into its wrapper. Structurally pinned fields stay pinned, while `#[unpin]` fields are plain Refs.
+///
+/// This is generated by `#[RefAccessors(pin)]`.
+pub trait RefAccessorsPinned<Wrapped> {
+    fn to_wrapped_pinned(self) -> Wrapped;
+}
+
+/// Projects a RawRef into its wrapper of RawRefs, without creating any intermediate reference.
+///
+/// This is generated by `#[RefAccessors(raw)]`.
+pub trait RawRefAccessors<Wrapped> {
+    /// # Safety
+    ///
+    /// The pointer must point into an allocation large enough to hold the value.
+    /// The value itself does not need to be initialised.
+    unsafe fn to_wrapped_raw(self) -> Wrapped;
+}
+
+/// Projects a Ref into its wrapper without consuming it. The wrapper borrows from the Ref for `'b`.
+///
+/// This is generated by `#[RefAccessors]` alongside `RefAccessors`.
+pub trait RefAccessorsMut<'b, Wrapped> {
+    fn wrap(&'b mut self) -> Wrapped;
+}
+
+impl<F1, F2, A: ?Sized, B: ?Sized> RefFn<F1, F2, A, B>
 where
     F1: FnOnce(&A) -> &B,
     F2: FnOnce(&mut A) -> &mut B,
@@ -99,41 +336,362 @@ where
         RefFn {
             apply,
             apply_mut,
+            apply_owned: Unowned,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F1, F2, F3, A, B> RefFn<F1, F2, A, B, F3>
+where
+    F1: FnOnce(&A) -> &B,
+    F2: FnOnce(&mut A) -> &mut B,
+    F3: FnOnce(A) -> B,
+{
+    /// Creates a RefFn which may also be applied to Owned refs.
+    #[inline(always)]
+    pub fn new_owned(apply: F1, apply_mut: F2, apply_owned: F3) -> Self {
+        RefFn {
+            apply,
+            apply_mut,
+            apply_owned,
             _marker: PhantomData,
         }
     }
 }
 
-impl<'a, F1, F2, A, B: 'a> RefFn<F1, F2, A, B>
+impl<'a, F1, F2, F3, A, B: 'a> RefFn<F1, F2, A, B, F3>
 where
     F1: FnOnce(&A) -> &B,
     F2: FnOnce(&mut A) -> &mut B,
+    F3: FnOnce(A) -> B,
+{
+    #[inline(always)]
+    pub fn ap<T: AccessType>(self, x: Ref<'a, A, T>) -> Ref<'a, B, T> {
+        T::_apply(self, x)
+    }
+}
+
+/// Applying a RefFn only needs it to work for borrows of lifetime `'a`, so that composed RefFns may pass through types which
+/// only live for `'a`.
+impl<'a, F1, F2, A: ?Sized + 'a, B: ?Sized + 'a> RefFn<F1, F2, A, B>
+where
+    F1: FnOnce(&'a A) -> &'a B,
+    F2: FnOnce(&'a mut A) -> &'a mut B,
 {
     #[inline(always)]
     pub fn ap<T: RefType>(self, x: Ref<'a, A, T>) -> Ref<'a, B, T> {
-        T::_apply_once(self, x)
+        let (apply, apply_mut) = (self.apply, self.apply_mut);
+        T::_fork(
+            x,
+            |x| unsafe { Ref::new_unchecked(apply(x)) },
+            |x| unsafe { Ref::new_unchecked_mut(apply_mut(x)) },
+        )
+    }
+
+    /// Composes two RefFns, applying `self` and then `other`.
+    ///
+    /// The composed RefFn is only applicable to borrows of lifetime `'a`, so the intermediate type `B` need only outlive
+    /// the Ref it is applied to.
+    #[inline(always)]
+    pub fn then<G1, G2, C: ?Sized + 'a>(
+        self,
+        other: RefFn<G1, G2, B, C>,
+    ) -> RefFn<impl FnOnce(&'a A) -> &'a C, impl FnOnce(&'a mut A) -> &'a mut C, A, C>
+    where
+        G1: FnOnce(&'a B) -> &'a C,
+        G2: FnOnce(&'a mut B) -> &'a mut C,
+    {
+        let (apply, apply_mut) = (self.apply, self.apply_mut);
+        let (then, then_mut) = (other.apply, other.apply_mut);
+        RefFn {
+            apply: move |x| then(apply(x)),
+            apply_mut: move |x| then_mut(apply_mut(x)),
+            apply_owned: Unowned,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F1, F2, A: ?Sized, B: ?Sized> RefFn<F1, F2, A, B>
+where
+    F1: FnOnce(&A) -> &B,
+    F2: FnOnce(&mut A) -> &mut B,
+{
+    /// Boxes the RefFn, so that it can be named, stored and applied more than once.
+    #[inline(always)]
+    pub fn boxed(self) -> DynRefFn<A, B>
+    where
+        F1: Fn(&A) -> &B + 'static,
+        F2: Fn(&mut A) -> &mut B + 'static,
+    {
+        DynRefFn::new(self.apply, self.apply_mut)
+    }
+}
+
+impl<A: ?Sized> RefFn<fn(&A) -> &A, fn(&mut A) -> &mut A, A, A> {
+    /// The RefFn which returns its argument.
+    #[inline(always)]
+    pub fn identity() -> Self {
+        RefFn::new(|x| x, |x| x)
+    }
+}
+
+impl Access for Shared {
+    type Owner<T: ?Sized> = ();
+
+    #[inline(always)]
+    unsafe fn _owner<T: ?Sized>(_value: NonNull<T>) {}
+
+    #[inline(always)]
+    unsafe fn _from_owned<T: ?Sized>(_x: OwnedBox<T>) {
+        unreachable!("only Owned refs own their value")
+    }
+
+    #[inline(always)]
+    fn _fork_any<'a, F1, F2, F3, F4, T: ?Sized, R>(
+        x: Ref<'a, T, Self>,
+        shared: F1,
+        _unique: F2,
+        _owned: F3,
+        _cell: F4,
+    ) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+        F3: FnOnce(OwnedBox<T>) -> R,
+        F4: FnOnce(NonNull<T>) -> R,
+    {
+        shared(x.into_ref())
+    }
+}
+
+impl FieldType for Shared {
+    #[inline(always)]
+    fn _fork_fields<'a, F1, F2, F3, T: ?Sized, R>(
+        x: Ref<'a, T, Self>,
+        shared: F1,
+        _unique: F2,
+        _cell: F3,
+    ) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+        F3: FnOnce(NonNull<T>) -> R,
+    {
+        shared(x.into_ref())
+    }
+}
+
+impl AccessType for Shared {
+    #[inline(always)]
+    fn _apply<F1, F2, F3, A, B>(f: RefFn<F1, F2, A, B, F3>, x: Ref<A, Self>) -> Ref<B, Self>
+    where
+        F1: FnOnce(&A) -> &B,
+        F2: FnOnce(&mut A) -> &mut B,
+        F3: FnOnce(A) -> B,
+    {
+        Ref::new((f.apply)(x.into_ref()))
+    }
+
+    #[inline(always)]
+    fn _fork_all<'a, F1, F2, F3, T: ?Sized, R>(
+        x: Ref<'a, T, Self>,
+        shared: F1,
+        _unique: F2,
+        _owned: F3,
+    ) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+        F3: FnOnce(OwnedBox<T>) -> R,
+    {
+        shared(x.into_ref())
     }
 }
 
 impl RefType for Shared {
+    const KIND: RefKind = RefKind::Shared;
+
+    type Select<A, B> = A;
+    type Native<'a, T: ?Sized + 'a> = &'a T;
+
+    #[inline(always)]
+    fn _select<'a, F1, F2, T: ?Sized, A, B>(x: Ref<'a, T, Self>, shared: F1, _unique: F2) -> A
+    where
+        F1: FnOnce(&'a T) -> A,
+        F2: FnOnce(&'a mut T) -> B,
+    {
+        shared(x.into_ref())
+    }
+
+    #[inline(always)]
+    fn _into_native<'a, T: ?Sized>(x: Ref<'a, T, Self>) -> &'a T {
+        x.into_ref()
+    }
+
+    #[inline(always)]
+    fn _from_native<'a, T: ?Sized>(x: Self::Native<'a, T>) -> Ref<'a, T, Self> {
+        Shared::new(x)
+    }
+
+    #[inline(always)]
+    fn _apply_once<F1, F2, A: ?Sized, B: ?Sized>(
+        f: RefFn<F1, F2, A, B>,
+        x: Ref<A, Self>,
+    ) -> Ref<B, Self>
+    where
+        F1: FnOnce(&A) -> &B,
+        F2: FnOnce(&mut A) -> &mut B,
+    {
+        Ref::new((f.apply)(x.into_ref()))
+    }
+
+    #[inline(always)]
+    fn _fork<'a, F1, F2, T: ?Sized, R>(x: Ref<'a, T, Self>, shared: F1, _unique: F2) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+    {
+        shared(x.into_ref())
+    }
+
+    #[inline(always)]
+    fn _choose<F1, F2, R>(shared: F1, _unique: F2) -> R
+    where
+        F1: FnOnce() -> R,
+        F2: FnOnce() -> R,
+    {
+        shared()
+    }
+}
+
+impl Access for Unique {
+    type Owner<T: ?Sized> = ();
+
+    #[inline(always)]
+    unsafe fn _owner<T: ?Sized>(_value: NonNull<T>) {}
+
+    #[inline(always)]
+    unsafe fn _from_owned<T: ?Sized>(_x: OwnedBox<T>) {
+        unreachable!("only Owned refs own their value")
+    }
+
+    #[inline(always)]
+    fn _fork_any<'a, F1, F2, F3, F4, T: ?Sized, R>(
+        x: Ref<'a, T, Self>,
+        _shared: F1,
+        unique: F2,
+        _owned: F3,
+        _cell: F4,
+    ) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+        F3: FnOnce(OwnedBox<T>) -> R,
+        F4: FnOnce(NonNull<T>) -> R,
+    {
+        unique(x.into_mut())
+    }
+}
+
+impl FieldType for Unique {
+    #[inline(always)]
+    fn _fork_fields<'a, F1, F2, F3, T: ?Sized, R>(
+        x: Ref<'a, T, Self>,
+        _shared: F1,
+        unique: F2,
+        _cell: F3,
+    ) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+        F3: FnOnce(NonNull<T>) -> R,
+    {
+        unique(x.into_mut())
+    }
+}
+
+impl AccessType for Unique {
     #[inline(always)]
-    fn _apply_once<F1, F2, A, B>(f: RefFn<F1, F2, A, B>, x: Ref<A, Self>) -> Ref<B, Self>
+    fn _apply<F1, F2, F3, A, B>(f: RefFn<F1, F2, A, B, F3>, x: Ref<A, Self>) -> Ref<B, Self>
     where
         F1: FnOnce(&A) -> &B,
         F2: FnOnce(&mut A) -> &mut B,
+        F3: FnOnce(A) -> B,
     {
-        Ref::new((f.apply)(x.as_ref()))
+        Ref::new((f.apply_mut)(x.into_mut()))
+    }
+
+    #[inline(always)]
+    fn _fork_all<'a, F1, F2, F3, T: ?Sized, R>(
+        x: Ref<'a, T, Self>,
+        _shared: F1,
+        unique: F2,
+        _owned: F3,
+    ) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+        F3: FnOnce(OwnedBox<T>) -> R,
+    {
+        unique(x.into_mut())
     }
 }
 
 impl RefType for Unique {
+    const KIND: RefKind = RefKind::Unique;
+
+    type Select<A, B> = B;
+    type Native<'a, T: ?Sized + 'a> = &'a mut T;
+
+    #[inline(always)]
+    fn _select<'a, F1, F2, T: ?Sized, A, B>(x: Ref<'a, T, Self>, _shared: F1, unique: F2) -> B
+    where
+        F1: FnOnce(&'a T) -> A,
+        F2: FnOnce(&'a mut T) -> B,
+    {
+        unique(x.into_mut())
+    }
+
+    #[inline(always)]
+    fn _into_native<'a, T: ?Sized>(x: Ref<'a, T, Self>) -> &'a mut T {
+        x.into_mut()
+    }
+
+    #[inline(always)]
+    fn _from_native<'a, T: ?Sized>(x: Self::Native<'a, T>) -> Ref<'a, T, Self> {
+        Unique::new(x)
+    }
+
     #[inline(always)]
-    fn _apply_once<F1, F2, A, B>(f: RefFn<F1, F2, A, B>, mut x: Ref<A, Self>) -> Ref<B, Self>
+    fn _apply_once<F1, F2, A: ?Sized, B: ?Sized>(
+        f: RefFn<F1, F2, A, B>,
+        x: Ref<A, Self>,
+    ) -> Ref<B, Self>
     where
         F1: FnOnce(&A) -> &B,
         F2: FnOnce(&mut A) -> &mut B,
     {
-        Ref::new((f.apply_mut)(x.as_mut()))
+        Ref::new((f.apply_mut)(x.into_mut()))
+    }
+
+    #[inline(always)]
+    fn _fork<'a, F1, F2, T: ?Sized, R>(x: Ref<'a, T, Self>, _shared: F1, unique: F2) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+    {
+        unique(x.into_mut())
+    }
+
+    #[inline(always)]
+    fn _choose<F1, F2, R>(_shared: F1, unique: F2) -> R
+    where
+        F1: FnOnce() -> R,
+        F2: FnOnce() -> R,
+    {
+        unique()
     }
 }
 
@@ -142,7 +700,8 @@ impl Shared {
     #[inline(always)]
     pub fn new<'a, T: ?Sized>(t: &'a T) -> Ref<'a, T, Shared> {
         Ref {
-            value: t,
+            value: NonNull::from(t),
+            owner: (),
             ty: PhantomData,
         }
     }
@@ -153,13 +712,14 @@ impl Unique {
     #[inline(always)]
     pub fn new<'a, T: ?Sized>(t: &'a mut T) -> Ref<'a, T, Unique> {
         Ref {
-            value: t,
+            value: NonNull::from(t),
+            owner: (),
             ty: PhantomData,
         }
     }
 }
 
-impl<'a, T: ?Sized, S: RefType> Deref for Ref<'a, T, S> {
+impl<'a, T: ?Sized, S: AccessType> Deref for Ref<'a, T, S> {
     type Target = T;
     #[inline(always)]
     fn deref(&self) -> &T {
@@ -174,31 +734,216 @@ impl<'a, T: ?Sized> DerefMut for Ref<'a, T, Unique> {
     }
 }
 
-impl<'a, T: ?Sized, S: RefType> Ref<'a, T, S> {
-    /// Converts the Ref into a borrow. This works for both shared and unique references.
+impl<'a, T: ?Sized, S: AccessType> Ref<'a, T, S> {
+    /// Borrows the value behind the Ref. This works for shared, unique and owned references.
+    #[allow(clippy::should_implement_trait)]
+    #[inline(always)]
+    pub fn as_ref(&self) -> &T {
+        unsafe { self.value.as_ref() }
+    }
+
+    /// Borrows the Ref as a shared Ref.
+    #[inline(always)]
+    pub fn as_shared(&self) -> Ref<'_, T, Shared> {
+        Shared::new(self.as_ref())
+    }
+
+    /// Returns a raw pointer to the value, like `Rc::as_ptr`.
+    #[inline(always)]
+    pub fn as_ptr(this: &Self) -> *const T {
+        this.value.as_ptr()
+    }
+
+    /// Returns whether two Refs point to the same value, like `Rc::ptr_eq`. The types of the borrows may differ.
+    ///
+    /// As with `std::ptr::addr_eq`, only the addresses are compared, and not the metadata of unsized values.
+    #[inline(always)]
+    pub fn ptr_eq<S2: AccessType>(this: &Self, other: &Ref<'_, T, S2>) -> bool {
+        std::ptr::addr_eq(Ref::as_ptr(this), Ref::as_ptr(other))
+    }
+}
+
+impl<'a, T: ?Sized, S: Access> Ref<'a, T, S> {
+    /// # Safety
+    ///
+    /// `S` must be `Shared`.
     #[inline(always)]
-    pub fn as_ref(&self) -> &'a T {
-        self.value
+    pub(crate) unsafe fn new_unchecked(value: &'a T) -> Ref<'a, T, S> {
+        let value = NonNull::from(value);
+        Ref {
+            value,
+            owner: S::_owner(value),
+            ty: PhantomData,
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `S` must be `Shared` or `Unique`.
+    #[inline(always)]
+    pub(crate) unsafe fn new_unchecked_mut(value: &'a mut T) -> Ref<'a, T, S> {
+        let value = NonNull::from(value);
+        Ref {
+            value,
+            owner: S::_owner(value),
+            ty: PhantomData,
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `S` must be `Owned`.
+    #[inline(always)]
+    pub(crate) unsafe fn new_unchecked_box(value: Box<T>) -> Ref<'a, T, S> {
+        Ref::new_unchecked_owned(OwnedBox::from_box(value))
+    }
+
+    /// # Safety
+    ///
+    /// `S` must be `Owned`.
+    #[inline(always)]
+    pub(crate) unsafe fn new_unchecked_owned(value: OwnedBox<T>) -> Ref<'a, T, S> {
+        Ref {
+            value: value.value,
+            owner: S::_from_owned(value),
+            ty: PhantomData,
+        }
     }
 
     /// UNSAFE. Do not use unless you know exactly what you are doing.
     /// Use of this to create a Unique reference (`Ref<'a, T, Unique>`) is undefined behaviour.
     ///
-    /// This is only public so that ref_clone_derive can call it.
+    /// This is only public so that ref_clone_derive can call it. Use `Ref::map` or `Ref::filter_map` to write projections instead.
+    ///
+    /// # Safety
+    ///
+    /// `S` must be `Shared`.
+    #[doc(hidden)]
+    #[deprecated(note = "use `Ref::map` or `Ref::filter_map` to project a Ref")]
     #[inline(always)]
     pub unsafe fn __new_unsafe(value: &'a T) -> Ref<'a, T, S> {
+        Ref::new_unchecked(value)
+    }
+
+    /// UNSAFE. Do not use unless you know exactly what you are doing.
+    ///
+    /// This is only public so that ref_clone_derive can call it. Use `Ref::map` or `Ref::filter_map` to write projections instead.
+    ///
+    /// # Safety
+    ///
+    /// `S` must be `Shared` or `Unique`.
+    #[doc(hidden)]
+    #[deprecated(note = "use `Ref::map` or `Ref::filter_map` to project a Ref")]
+    #[inline(always)]
+    pub unsafe fn __new_unsafe_mut(value: &'a mut T) -> Ref<'a, T, S> {
+        Ref::new_unchecked_mut(value)
+    }
+
+    /// UNSAFE. Do not use unless you know exactly what you are doing.
+    ///
+    /// This is only public so that ref_clone_derive can call it.
+    ///
+    /// # Safety
+    ///
+    /// `S` must be `CellShared`, and `value` must be valid as a `&'a Cell<T>`.
+    #[inline(always)]
+    pub unsafe fn __new_unsafe_cell(value: *mut T) -> Ref<'a, T, S> {
+        let value = NonNull::new_unchecked(value);
         Ref {
             value,
+            owner: S::_owner(value),
             ty: PhantomData,
         }
     }
 }
 
+impl<'a, T: ?Sized, S: RefType> Ref<'a, T, S> {
+    /// Converts the Ref into a borrow for the whole lifetime of the Ref. This works for both shared and unique references.
+    #[inline(always)]
+    pub fn into_ref(self) -> &'a T {
+        unsafe { self.value.as_ref() }
+    }
+
+    /// Reborrows the Ref, so that it can be projected without being consumed.
+    ///
+    /// The original Ref may be used again once the reborrow is dropped.
+    #[inline(always)]
+    pub fn reborrow(&mut self) -> Ref<'_, T, S> {
+        Ref {
+            value: self.value,
+            owner: unsafe { S::_owner(self.value) },
+            ty: PhantomData,
+        }
+    }
+
+    /// Converts the Ref into a Shared Ref for the whole lifetime of the Ref.
+    #[inline(always)]
+    pub fn into_shared(self) -> Ref<'a, T, Shared> {
+        Shared::new(self.into_ref())
+    }
+
+    /// Weakens the type of the borrow into `S2`, such as a Unique Ref into a Shared Ref.
+    #[inline(always)]
+    pub fn downgrade<S2: RefType>(self) -> Ref<'a, T, S2>
+    where
+        S: Weaker<S2>,
+    {
+        S::_weaken(self)
+    }
+
+    /// Projects the Ref with `f` if it is Shared, or with `f_mut` if it is Unique.
+    ///
+    /// This is an associated function, like `std::cell::Ref::map`, so that it does not hide a `map` method of `T`.
+    /// It is used as `Ref::map(x, |x| &x.0, |x| &mut x.0)`.
+    #[inline(always)]
+    pub fn map<U: ?Sized, F1, F2>(this: Self, f: F1, f_mut: F2) -> Ref<'a, U, S>
+    where
+        F1: FnOnce(&T) -> &U,
+        F2: FnOnce(&mut T) -> &mut U,
+    {
+        S::_apply_once(RefFn::new(f, f_mut), this)
+    }
+
+    /// Projects the Ref like `map`, giving back the original Ref if the projection returns `None`.
+    #[inline(always)]
+    pub fn filter_map<U: ?Sized, F1, F2>(
+        this: Self,
+        f: F1,
+        f_mut: F2,
+    ) -> Result<Ref<'a, U, S>, Self>
+    where
+        F1: FnOnce(&T) -> Option<&U>,
+        F2: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        // Like `std::cell::RefMut::filter_map`, the original Ref is only used again if no borrow was returned.
+        let copy = Ref {
+            value: this.value,
+            owner: unsafe { S::_owner(this.value) },
+            ty: PhantomData,
+        };
+        TryRefFn::new(f, f_mut).ap(copy).ok_or(this)
+    }
+}
+
 impl<'a, T: ?Sized> Ref<'a, T, Unique> {
-    /// Converts the Ref into a mutable borrow. This only works for shared references.
+    /// Mutably borrows the value behind the Ref. This only works for unique references.
+    #[allow(clippy::should_implement_trait)]
     #[inline(always)]
-    pub fn as_mut(&mut self) -> &'a mut T {
-        unsafe { (self.value as *const T as *mut T).as_mut().unwrap() }
+    pub fn as_mut(&mut self) -> &mut T {
+        unsafe { self.value.as_mut() }
+    }
+
+    /// Converts the Ref into a mutable borrow for the whole lifetime of the Ref. This only works for unique references.
+    #[inline(always)]
+    pub fn into_mut(mut self) -> &'a mut T {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<'a, T: ?Sized> From<Ref<'a, T, Unique>> for Ref<'a, T, Shared> {
+    #[inline(always)]
+    fn from(x: Ref<'a, T, Unique>) -> Self {
+        x.into_shared()
     }
 }
 
@@ -225,26 +970,145 @@ impl<'a, T: ?Sized, S: RefType> Ref<'a, T, S> {
     }
 }
 
-impl<'a, T: std::fmt::Debug + ?Sized, S: RefType> std::fmt::Debug for Ref<'a, T, S> {
+unsafe impl<'a, T: Sync + ?Sized> Send for Ref<'a, T, Shared> {}
+unsafe impl<'a, T: Sync + ?Sized> Sync for Ref<'a, T, Shared> {}
+unsafe impl<'a, T: Send + ?Sized> Send for Ref<'a, T, Unique> {}
+unsafe impl<'a, T: Sync + ?Sized> Sync for Ref<'a, T, Unique> {}
+
+impl<'a, 'b, A: PartialEq<B> + ?Sized, B: ?Sized, S: AccessType, S2: AccessType>
+    PartialEq<Ref<'b, B, S2>> for Ref<'a, A, S>
+{
+    #[inline(always)]
+    fn eq(&self, other: &Ref<'b, B, S2>) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl<'a, 'b, A: PartialEq<B> + ?Sized, B: ?Sized, S: AccessType> PartialEq<&'b B>
+    for Ref<'a, A, S>
+{
+    #[inline(always)]
+    fn eq(&self, other: &&'b B) -> bool {
+        self.as_ref() == *other
+    }
+}
+
+impl<'a, T: Eq + ?Sized, S: AccessType> Eq for Ref<'a, T, S> {}
+
+impl<'a, T: PartialOrd + ?Sized, S: AccessType> PartialOrd for Ref<'a, T, S> {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.as_ref().partial_cmp(other.as_ref())
+    }
+}
+
+impl<'a, T: Ord + ?Sized, S: AccessType> Ord for Ref<'a, T, S> {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_ref().cmp(other.as_ref())
+    }
+}
+
+impl<'a, T: Hash + ?Sized, S: AccessType> Hash for Ref<'a, T, S> {
+    #[inline(always)]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state)
+    }
+}
+
+impl<'a, T: std::fmt::Debug + ?Sized, S: AccessType> std::fmt::Debug for Ref<'a, T, S> {
+    #[inline(always)]
+    fn fmt(
+        &self,
+        formatter: &mut std::fmt::Formatter<'_>,
+    ) -> std::result::Result<(), std::fmt::Error> {
+        self.as_ref().fmt(formatter)
+    }
+}
+
+impl<'a, T: std::fmt::Display + ?Sized, S: AccessType> std::fmt::Display for Ref<'a, T, S> {
     #[inline(always)]
     fn fmt(
         &self,
         formatter: &mut std::fmt::Formatter<'_>,
     ) -> std::result::Result<(), std::fmt::Error> {
-        self.value.fmt(formatter)
+        self.as_ref().fmt(formatter)
     }
 }
 
-impl<'a, T: std::fmt::Display + ?Sized, S: RefType> std::fmt::Display for Ref<'a, T, S> {
+impl<'a, T: ?Sized, S: AccessType> std::fmt::Pointer for Ref<'a, T, S> {
     #[inline(always)]
     fn fmt(
         &self,
         formatter: &mut std::fmt::Formatter<'_>,
     ) -> std::result::Result<(), std::fmt::Error> {
-        self.value.fmt(formatter)
+        std::fmt::Pointer::fmt(&Ref::as_ptr(self), formatter)
+    }
+}
+
+impl<'a, T: ?Sized> Clone for Ref<'a, T, Shared> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
     }
 }
 
+impl<'a, T: ?Sized> Copy for Ref<'a, T, Shared> {}
+
+impl<'a, T: ?Sized, S: AccessType> AsRef<T> for Ref<'a, T, S> {
+    #[inline(always)]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T: ?Sized> AsMut<T> for Ref<'a, T, Unique> {
+    #[inline(always)]
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T: ?Sized, S: AccessType> Borrow<T> for Ref<'a, T, S> {
+    #[inline(always)]
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T: ?Sized> BorrowMut<T> for Ref<'a, T, Unique> {
+    #[inline(always)]
+    fn borrow_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+macro_rules! assign_op {
+    ($($trait:ident $method:ident),*) => {
+        $(
+            impl<'a, T: std::ops::$trait<Rhs> + ?Sized, Rhs> std::ops::$trait<Rhs> for Ref<'a, T, Unique> {
+                #[inline(always)]
+                fn $method(&mut self, rhs: Rhs) {
+                    (**self).$method(rhs)
+                }
+            }
+        )*
+    };
+}
+
+assign_op!(
+    AddAssign add_assign,
+    SubAssign sub_assign,
+    MulAssign mul_assign,
+    DivAssign div_assign,
+    RemAssign rem_assign,
+    BitAndAssign bitand_assign,
+    BitOrAssign bitor_assign,
+    BitXorAssign bitxor_assign,
+    ShlAssign shl_assign,
+    ShrAssign shr_assign
+);
+
 mod private {
     use crate::*;
 
@@ -252,11 +1116,47 @@ mod private {
 
     impl Sealed for Shared {}
     impl Sealed for Unique {}
-    impl<T, S: RefType> Sealed for Ref<'_, T, S> {}
+    impl Sealed for Owned {}
+    impl Sealed for CellShared {}
+    impl Sealed for Option<std::convert::Infallible> {}
+    impl<E> Sealed for Result<std::convert::Infallible, E> {}
+    impl<T, S: Access> Sealed for Ref<'_, T, S> {}
 }
 
+mod cell;
+mod collections;
+mod deref;
+mod dyn_ref_fn;
+mod guard;
+mod kind;
+mod lens;
+mod lock;
+mod map;
+mod option;
+mod owned;
+mod owned_ref;
+mod pinned;
+mod raw;
+mod slice;
+mod split;
 mod traits;
+mod try_ref_fn;
+mod tuple;
+pub use cell::CellIter;
+pub use collections::{LinkedListIter, VecDequeIter};
+pub use dyn_ref_fn::DynRefFn;
+pub use guard::*;
+pub use kind::*;
+pub use lens::*;
+pub use lock::*;
+pub use map::*;
+pub use owned::{OwnedAlloc, OwnedBox, OwnedIter};
+pub use owned_ref::*;
+pub use raw::RawRef;
+pub use split::SplitBorrows;
 pub use traits::*;
+pub use try_ref_fn::*;
+pub use tuple::Tuple;
 
 /* =============== Specific implementation of traits =============== */
 
@@ -264,8 +1164,17 @@ impl<T> IndexRef<usize> for [T] {
     // Array ref
     type Output = T;
     #[inline(always)]
-    fn index_ref<'a, S: RefType>(self: Ref<'a, Self, S>, i: usize) -> Ref<'a, T, S> {
-        unsafe { Ref::__new_unsafe(&self.value[i]) }
+    fn index_ref<'a, S: AccessType>(this: Ref<'a, Self, S>, i: usize) -> Ref<'a, T, S> {
+        unsafe {
+            S::_fork_all(
+                this,
+                |x| Ref::new_unchecked(&x[i]),
+                |x| Ref::new_unchecked_mut(&mut x[i]),
+                |x| {
+                    Ref::new_unchecked_owned(OwnedBox::from_vec_element(x.into_box().into_vec(), i))
+                },
+            )
+        }
     }
 }
 
@@ -273,57 +1182,172 @@ impl<T, const N: usize> IndexRef<usize> for [T; N] {
     // Array ref
     type Output = T;
     #[inline(always)]
-    fn index_ref<'a, S: RefType>(self: Ref<'a, Self, S>, i: usize) -> Ref<'a, T, S> {
-        unsafe { Ref::__new_unsafe(&self.value[i]) }
+    fn index_ref<'a, S: AccessType>(this: Ref<'a, Self, S>, i: usize) -> Ref<'a, T, S> {
+        RefFn::new_owned(
+            |x: &Self| &x[i],
+            |x: &mut Self| &mut x[i],
+            |x: Self| {
+                IntoIterator::into_iter(x)
+                    .nth(i)
+                    .expect("index out of bounds")
+            },
+        )
+        .ap(this)
     }
 }
 
 impl<T> DerefRef for Box<T> {
     type Target = T;
     #[inline(always)]
-    fn deref_ref<'a, S: RefType>(self: Ref<'a, Self, S>) -> Ref<'a, T, S> {
-        unsafe { Ref::__new_unsafe(self.as_ref().deref()) }
+    fn deref_ref<'a, S: RefType>(this: Ref<'a, Self, S>) -> Ref<'a, T, S> {
+        RefFn::new(|x: &Self| x.deref(), |x: &mut Self| x.deref_mut()).ap(this)
     }
 }
 
-pub struct RefIter<'a, T, S: RefType> {
-    iter: Iter<'a, T>,
-    _marker: PhantomData<S>,
+/// An iterator over the elements of a collection as Refs, such as the one created by iterating over a Ref to a slice.
+///
+/// `I1`, `I2` and `I3` are the iterators of the collection used for Shared, Unique and Owned refs.
+pub struct RefIter<'a, T, S: AccessType, I1 = Iter<'a, T>, I2 = IterMut<'a, T>, I3 = OwnedIter<T>> {
+    iter: ForkAllIter<I1, I2, I3>,
+    _marker: PhantomData<Ref<'a, T, S>>,
+}
+
+/// The iterator of a collection as Shared or Unique borrows or by value. Which one is used always matches `S`.
+enum ForkAllIter<I1, I2, I3> {
+    Shared(I1),
+    Unique(I2),
+    Owned(I3),
+}
+
+impl<'a, T, S: AccessType, I1, I2, I3> RefIter<'a, T, S, I1, I2, I3> {
+    #[inline(always)]
+    pub(crate) fn new<C: ?Sized>(
+        x: Ref<'a, C, S>,
+        shared: impl FnOnce(&'a C) -> I1,
+        unique: impl FnOnce(&'a mut C) -> I2,
+        owned: impl FnOnce(OwnedBox<C>) -> I3,
+    ) -> Self {
+        RefIter {
+            iter: S::_fork_all(
+                x,
+                |x| ForkAllIter::Shared(shared(x)),
+                |x| ForkAllIter::Unique(unique(x)),
+                |x| ForkAllIter::Owned(owned(x)),
+            ),
+            _marker: PhantomData,
+        }
+    }
 }
 
-impl<'a, T, S: RefType> Iterator for RefIter<'a, T, S> {
+/// Calls `$next` on the underlying iterator of a `RefIter`, wrapping the item in a Ref of the same type.
+macro_rules! ref_iter_next {
+    ($iter:expr, $next:ident) => {
+        unsafe {
+            match $iter {
+                ForkAllIter::Shared(iter) => iter.$next().map(|a| Ref::new_unchecked(a)),
+                ForkAllIter::Unique(iter) => iter.$next().map(|a| Ref::new_unchecked_mut(a)),
+                ForkAllIter::Owned(iter) => iter.$next().map(|a| Ref::new_unchecked_owned(a)),
+            }
+        }
+    };
+}
+
+impl<'a, T: 'a, S: AccessType, I1, I2, I3> Iterator for RefIter<'a, T, S, I1, I2, I3>
+where
+    I1: Iterator<Item = &'a T>,
+    I2: Iterator<Item = &'a mut T>,
+    I3: Iterator<Item = OwnedBox<T>>,
+{
     type Item = Ref<'a, T, S>;
     #[inline(always)]
     fn next(&mut self) -> Option<Self::Item> {
+        ref_iter_next!(&mut self.iter, next)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.iter {
+            ForkAllIter::Shared(iter) => iter.size_hint(),
+            ForkAllIter::Unique(iter) => iter.size_hint(),
+            ForkAllIter::Owned(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<'a, T: 'a, S: AccessType, I1, I2, I3> DoubleEndedIterator for RefIter<'a, T, S, I1, I2, I3>
+where
+    I1: DoubleEndedIterator<Item = &'a T>,
+    I2: DoubleEndedIterator<Item = &'a mut T>,
+    I3: DoubleEndedIterator<Item = OwnedBox<T>>,
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        ref_iter_next!(&mut self.iter, next_back)
+    }
+}
+
+impl<'a, T: 'a, S: AccessType, I1, I2, I3> ExactSizeIterator for RefIter<'a, T, S, I1, I2, I3>
+where
+    I1: ExactSizeIterator<Item = &'a T>,
+    I2: ExactSizeIterator<Item = &'a mut T>,
+    I3: ExactSizeIterator<Item = OwnedBox<T>>,
+{
+}
+
+impl<'a, T: 'a, S: AccessType, I1, I2, I3> FusedIterator for RefIter<'a, T, S, I1, I2, I3>
+where
+    I1: FusedIterator<Item = &'a T>,
+    I2: FusedIterator<Item = &'a mut T>,
+    I3: FusedIterator<Item = OwnedBox<T>>,
+{
+}
+
+impl<'a, T, S: AccessType, const N: usize> Ref<'a, [T; N], S> {
+    /// Converts a Ref to an array into a Ref to a slice.
+    #[inline(always)]
+    fn unsize(self) -> Ref<'a, [T], S> {
         unsafe {
-            match self.iter.next() {
-                Some(a) => Some(Ref::__new_unsafe(a)),
-                None => None,
-            }
+            S::_fork_all(
+                self,
+                |x| Ref::new_unchecked(x as &[T]),
+                |x| Ref::new_unchecked_mut(x as &mut [T]),
+                |x| Ref::new_unchecked_owned(x.unsize()),
+            )
         }
     }
 }
 
+impl<'a, T, S: AccessType, const N: usize> From<Ref<'a, [T; N], S>> for Ref<'a, [T], S> {
+    #[inline(always)]
+    fn from(x: Ref<'a, [T; N], S>) -> Self {
+        x.unsize()
+    }
+}
+
 impl<'a, T: 'a> IntoIteratorRef<'a> for [T] {
     type Item = T;
-    type IntoIter<S: RefType> = RefIter<'a, T, S>;
+    type IntoIter<S: AccessType> = RefIter<'a, T, S>;
     #[inline(always)]
-    fn into_iter_ref<S: RefType>(self: Ref<'a, Self, S>) -> Self::IntoIter<S> {
-        RefIter {
-            iter: self.as_ref().into_iter(),
-            _marker: PhantomData,
-        }
+    fn into_iter_ref<S: AccessType>(this: Ref<'a, Self, S>) -> Self::IntoIter<S> {
+        RefIter::new(
+            this,
+            |x| x.iter(),
+            |x| x.iter_mut(),
+            |x| OwnedIter::new(x.into_box().into_vec()),
+        )
     }
 }
 
 impl<'a, T: 'a, const N: usize> IntoIteratorRef<'a> for [T; N] {
     type Item = T;
-    type IntoIter<S: RefType> = RefIter<'a, T, S>;
+    type IntoIter<S: AccessType> = RefIter<'a, T, S>;
     #[inline(always)]
-    fn into_iter_ref<S: RefType>(self: Ref<'a, Self, S>) -> Self::IntoIter<S> {
-        RefIter {
-            iter: self.as_ref().into_iter(),
-            _marker: PhantomData,
-        }
+    fn into_iter_ref<S: AccessType>(this: Ref<'a, Self, S>) -> Self::IntoIter<S> {
+        RefIter::new(
+            this.unsize(),
+            |x| x.iter(),
+            |x| x.iter_mut(),
+            |x| OwnedIter::new(x.into_box().into_vec()),
+        )
     }
 }
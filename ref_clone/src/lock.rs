@@ -0,0 +1,225 @@
+use crate::*;
+use std::cell;
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::LockResult;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::sync::RwLockReadGuard;
+use std::sync::RwLockWriteGuard;
+
+/// A lock guard which gives access to the locked value as a `Ref<'_, T, S>`.
+///
+/// This is returned by `borrow_kind` and `lock_kind`. Like the guards of the standard library, it derefs to the value
+/// itself, as `Deref` must return a reference and can not return a `Ref` borrowing from the guard.
+/// Code which is generic over `S` should use `reborrow`, which gives the `Ref<'_, T, S>`:
+///
+/// ```
+/// # use ref_clone::*;
+/// # use std::cell::RefCell;
+/// fn first<S: RefType>(cell: &RefCell<Vec<u8>>) -> u8 {
+///     let mut guard = cell.borrow_kind::<S>();
+///     let x: Ref<'_, u8, S> = guard.reborrow().index_ref(0);
+///     *x
+/// }
+/// # assert_eq!(first::<Unique>(&RefCell::new(vec![1])), 1);
+/// ```
+pub struct LockRef<'a, T: ?Sized, S: RefType>(GuardRef<'a, LockGuard<'a, T>, T, S>);
+
+/// The guard of a `RefCell`, `Mutex` or `RwLock`, which is kept to release the lock when dropped.
+///
+/// This is the guard of the `GuardRef` returned by `LockRef::map` and `LockRef::into_guard_ref`.
+pub struct LockGuard<'a, T: ?Sized>(Guard<'a, T>);
+
+enum Guard<'a, T: ?Sized> {
+    Cell(cell::Ref<'a, T>),
+    CellMut(cell::RefMut<'a, T>),
+    Mutex(MutexGuard<'a, T>),
+    Read(RwLockReadGuard<'a, T>),
+    Write(RwLockWriteGuard<'a, T>),
+}
+
+impl<'a, T: ?Sized> Deref for LockGuard<'a, T> {
+    type Target = T;
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        match &self.0 {
+            Guard::Cell(x) => x,
+            Guard::CellMut(x) => x,
+            Guard::Mutex(x) => x,
+            Guard::Read(x) => x,
+            Guard::Write(x) => x,
+        }
+    }
+}
+
+// The pointer is derived from a unique borrow for exclusive locks, so that `lock_kind` may create Unique refs from them.
+unsafe impl<'a, T: ?Sized> StableGuard<Shared> for LockGuard<'a, T> {
+    #[inline(always)]
+    fn value_ptr(&mut self) -> NonNull<T> {
+        match &mut self.0 {
+            Guard::Cell(x) => NonNull::from(&**x),
+            Guard::CellMut(x) => NonNull::from(&mut **x),
+            Guard::Mutex(x) => NonNull::from(&mut **x),
+            Guard::Read(x) => NonNull::from(&**x),
+            Guard::Write(x) => NonNull::from(&mut **x),
+        }
+    }
+}
+
+impl<'a, T: ?Sized, S: RefType> LockRef<'a, T, S> {
+    /// # Safety
+    ///
+    /// `guard` must be an exclusive lock if `S` is `Unique`.
+    #[inline(always)]
+    unsafe fn new(guard: Guard<'a, T>) -> Self {
+        LockRef(GuardRef::new_unchecked(LockGuard(guard)))
+    }
+
+    /// Reborrows the locked value as a Ref for as long as the guard is borrowed.
+    #[inline(always)]
+    pub fn reborrow(&mut self) -> Ref<'_, T, S> {
+        self.0.reborrow()
+    }
+
+    /// Borrows the locked value as a shared Ref.
+    #[inline(always)]
+    pub fn as_shared(&self) -> Ref<'_, T, Shared> {
+        self.0.as_shared()
+    }
+
+    /// Applies a RefFn to the locked value, keeping the lock.
+    #[inline(always)]
+    pub fn map<F1, F2, U: ?Sized + 'a>(
+        self,
+        f: RefFn<F1, F2, T, U>,
+    ) -> GuardRef<'a, LockGuard<'a, T>, U, S>
+    where
+        F1: FnOnce(&T) -> &U,
+        F2: FnOnce(&mut T) -> &mut U,
+    {
+        self.0.map(f)
+    }
+
+    /// Converts the LockRef into a GuardRef which owns the lock.
+    #[inline(always)]
+    pub fn into_guard_ref(self) -> GuardRef<'a, LockGuard<'a, T>, T, S> {
+        self.0
+    }
+}
+
+impl<'a, T: ?Sized, S: RefType> Deref for LockRef<'a, T, S> {
+    type Target = T;
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for LockRef<'a, T, Unique> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<'a, 'b, T: ?Sized, S: RefType, Wrapped> RefAccessors<Wrapped> for &'b mut LockRef<'a, T, S>
+where
+    Ref<'b, T, S>: RefAccessors<Wrapped>,
+{
+    #[inline(always)]
+    fn to_wrapped(self) -> Wrapped {
+        self.reborrow().to_wrapped()
+    }
+}
+
+impl<'a, 'b, T: ?Sized, S: RefType, Wrapped> RefAccessorsMut<'b, Wrapped> for LockRef<'a, T, S>
+where
+    T: 'b,
+    Ref<'b, T, S>: RefAccessors<Wrapped>,
+{
+    #[inline(always)]
+    fn wrap(&'b mut self) -> Wrapped {
+        self.reborrow().to_wrapped()
+    }
+}
+
+impl<'a, T: ?Sized + fmt::Debug, S: RefType> fmt::Debug for LockRef<'a, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Creates a LockRef from the result of locking, keeping whether the lock was poisoned.
+///
+/// # Safety
+///
+/// See `LockRef::new`.
+#[inline(always)]
+unsafe fn from_result<'a, G, T: ?Sized, S: RefType>(
+    guard: LockResult<G>,
+    f: impl FnOnce(G) -> Guard<'a, T>,
+) -> LockResult<LockRef<'a, T, S>> {
+    match guard {
+        Ok(guard) => Ok(LockRef::new(f(guard))),
+        Err(err) => Err(PoisonError::new(LockRef::new(f(err.into_inner())))),
+    }
+}
+
+/// Borrows a RefCell with the type of the borrow as a type parameter.
+pub trait RefCellExt<T: ?Sized> {
+    /// Calls `borrow` for Shared refs and `borrow_mut` for Unique refs.
+    /// The value is accessed as a `Ref<'_, T, S>` with `LockRef::reborrow`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is already borrowed in a conflicting way.
+    fn borrow_kind<S: RefType>(&self) -> LockRef<'_, T, S>;
+}
+
+impl<T: ?Sized> RefCellExt<T> for RefCell<T> {
+    #[inline(always)]
+    fn borrow_kind<S: RefType>(&self) -> LockRef<'_, T, S> {
+        unsafe {
+            S::_choose(
+                || LockRef::new(Guard::Cell(self.borrow())),
+                || LockRef::new(Guard::CellMut(self.borrow_mut())),
+            )
+        }
+    }
+}
+
+/// Locks a Mutex with the type of the borrow as a type parameter.
+pub trait MutexExt<T: ?Sized> {
+    /// Calls `lock`. As a Mutex has no shared lock, Shared refs also take the exclusive lock.
+    /// The value is accessed as a `Ref<'_, T, S>` with `LockRef::reborrow`.
+    fn lock_kind<S: RefType>(&self) -> LockResult<LockRef<'_, T, S>>;
+}
+
+impl<T: ?Sized> MutexExt<T> for Mutex<T> {
+    #[inline(always)]
+    fn lock_kind<S: RefType>(&self) -> LockResult<LockRef<'_, T, S>> {
+        unsafe { from_result(self.lock(), Guard::Mutex) }
+    }
+}
+
+/// Locks a RwLock with the type of the borrow as a type parameter.
+pub trait RwLockExt<T: ?Sized> {
+    /// Calls `read` for Shared refs and `write` for Unique refs.
+    /// The value is accessed as a `Ref<'_, T, S>` with `LockRef::reborrow`.
+    fn lock_kind<S: RefType>(&self) -> LockResult<LockRef<'_, T, S>>;
+}
+
+impl<T: ?Sized> RwLockExt<T> for RwLock<T> {
+    #[inline(always)]
+    fn lock_kind<S: RefType>(&self) -> LockResult<LockRef<'_, T, S>> {
+        unsafe {
+            S::_choose(
+                || from_result(self.read(), Guard::Read),
+                || from_result(self.write(), Guard::Write),
+            )
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use crate::*;
+use std::borrow::Borrow;
+use std::collections::btree_map;
+use std::collections::hash_map;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::iter;
+use std::iter::FusedIterator;
+use std::ops::RangeBounds;
+
+/// The iterator of a map as Shared or Unique borrows. Which one is used always matches `S`.
+enum ForkIter<I1, I2> {
+    Shared(I1),
+    Unique(I2),
+}
+
+impl<I1, I2> ForkIter<I1, I2> {
+    #[inline(always)]
+    fn new<'a, T: ?Sized, S: RefType>(
+        x: Ref<'a, T, S>,
+        shared: impl FnOnce(&'a T) -> I1,
+        unique: impl FnOnce(&'a mut T) -> I2,
+    ) -> Self {
+        S::_fork(
+            x,
+            |x| ForkIter::Shared(shared(x)),
+            |x| ForkIter::Unique(unique(x)),
+        )
+    }
+}
+
+/// An iterator over the entries of a map, yielding each key with a Ref to its value.
+///
+/// This is created by iterating over a `Ref` to a `HashMap` or `BTreeMap`, or by `range_ref` on a `BTreeMap`.
+pub struct MapIter<'a, K: 'a, V: 'a, S: RefType, I1, I2> {
+    iter: ForkIter<I1, I2>,
+    _marker: PhantomData<(&'a K, Ref<'a, V, S>)>,
+}
+
+impl<'a, K: 'a, V: 'a, S: RefType, I1, I2> Iterator for MapIter<'a, K, V, S, I1, I2>
+where
+    I1: Iterator<Item = (&'a K, &'a V)>,
+    I2: Iterator<Item = (&'a K, &'a mut V)>,
+{
+    type Item = (&'a K, Ref<'a, V, S>);
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            match &mut self.iter {
+                ForkIter::Shared(iter) => iter.next().map(|(k, v)| (k, Ref::new_unchecked(v))),
+                ForkIter::Unique(iter) => iter.next().map(|(k, v)| (k, Ref::new_unchecked_mut(v))),
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.iter {
+            ForkIter::Shared(iter) => iter.size_hint(),
+            ForkIter::Unique(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<'a, K: 'a, V: 'a, S: RefType, I1, I2> ExactSizeIterator for MapIter<'a, K, V, S, I1, I2>
+where
+    I1: ExactSizeIterator<Item = (&'a K, &'a V)>,
+    I2: ExactSizeIterator<Item = (&'a K, &'a mut V)>,
+{
+}
+
+impl<'a, K: 'a, V: 'a, S: RefType, I1, I2> FusedIterator for MapIter<'a, K, V, S, I1, I2>
+where
+    I1: FusedIterator<Item = (&'a K, &'a V)>,
+    I2: FusedIterator<Item = (&'a K, &'a mut V)>,
+{
+}
+
+/// An iterator over the values of a map as Refs, created by `values_ref`.
+pub struct ValuesIter<'a, V: 'a, S: RefType, I1, I2> {
+    iter: ForkIter<I1, I2>,
+    _marker: PhantomData<Ref<'a, V, S>>,
+}
+
+impl<'a, V: 'a, S: RefType, I1, I2> Iterator for ValuesIter<'a, V, S, I1, I2>
+where
+    I1: Iterator<Item = &'a V>,
+    I2: Iterator<Item = &'a mut V>,
+{
+    type Item = Ref<'a, V, S>;
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            match &mut self.iter {
+                ForkIter::Shared(iter) => iter.next().map(|v| Ref::new_unchecked(v)),
+                ForkIter::Unique(iter) => iter.next().map(|v| Ref::new_unchecked_mut(v)),
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.iter {
+            ForkIter::Shared(iter) => iter.size_hint(),
+            ForkIter::Unique(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<'a, V: 'a, S: RefType, I1, I2> ExactSizeIterator for ValuesIter<'a, V, S, I1, I2>
+where
+    I1: ExactSizeIterator<Item = &'a V>,
+    I2: ExactSizeIterator<Item = &'a mut V>,
+{
+}
+
+impl<'a, V: 'a, S: RefType, I1, I2> FusedIterator for ValuesIter<'a, V, S, I1, I2>
+where
+    I1: FusedIterator<Item = &'a V>,
+    I2: FusedIterator<Item = &'a mut V>,
+{
+}
+
+pub type HashMapIter<'a, K, V, S> =
+    MapIter<'a, K, V, S, hash_map::Iter<'a, K, V>, hash_map::IterMut<'a, K, V>>;
+pub type HashMapValues<'a, K, V, S> =
+    ValuesIter<'a, V, S, hash_map::Values<'a, K, V>, hash_map::ValuesMut<'a, K, V>>;
+pub type BTreeMapIter<'a, K, V, S> =
+    MapIter<'a, K, V, S, btree_map::Iter<'a, K, V>, btree_map::IterMut<'a, K, V>>;
+pub type BTreeMapValues<'a, K, V, S> =
+    ValuesIter<'a, V, S, btree_map::Values<'a, K, V>, btree_map::ValuesMut<'a, K, V>>;
+pub type BTreeMapRange<'a, K, V, S> =
+    MapIter<'a, K, V, S, btree_map::Range<'a, K, V>, btree_map::RangeMut<'a, K, V>>;
+
+/// Owned maps are iterated by consuming the map, and yield their keys by value.
+/// Their entries are first collected into a vector, which the Owned refs to the values share.
+pub type HashMapOwnedIter<'a, K, V> = OwnedEntries<'a, K, V>;
+pub type HashMapOwnedValues<'a, V> = RefIter<'a, V, Owned>;
+pub type BTreeMapOwnedIter<'a, K, V> = OwnedEntries<'a, K, V>;
+pub type BTreeMapOwnedValues<'a, V> = RefIter<'a, V, Owned>;
+
+type OwnedEntries<'a, K, V> =
+    iter::Map<OwnedIter<(K, V)>, fn(OwnedBox<(K, V)>) -> (K, Ref<'a, V, Owned>)>;
+
+#[inline(always)]
+fn owned_entry<'a, K, V>(x: OwnedBox<(K, V)>) -> (K, Ref<'a, V, Owned>) {
+    unsafe {
+        let (x, alloc) = x.__into_parts();
+        (std::ptr::read(&x.0), alloc.__own(&mut x.1))
+    }
+}
+
+// Maps implement `IntoIterator` directly rather than `IntoIteratorRef`, as `IntoIteratorRef::IntoIter` must yield
+// `Ref<'a, Item, S>`, while iterating over a map yields each key alongside the Ref to its value.
+
+impl<'q, K, V, Q: ?Sized, H> IndexRef<&'q Q> for HashMap<K, V, H>
+where
+    K: Borrow<Q> + Eq + Hash,
+    Q: Eq + Hash,
+    H: BuildHasher,
+{
+    type Output = V;
+    /// # Panics
+    ///
+    /// Panics if the key is not in the map. Owned maps remove the value.
+    #[inline(always)]
+    fn index_ref<'a, S: AccessType>(this: Ref<'a, Self, S>, key: &'q Q) -> Ref<'a, V, S> {
+        this.get_ref(key).expect("key not found in map")
+    }
+}
+
+impl<'a, K: Eq + Hash, V, H: BuildHasher, S: AccessType> Ref<'a, HashMap<K, V, H>, S> {
+    /// Projects onto the value of a key, like `get` and `get_mut`. Owned maps remove the value.
+    #[inline(always)]
+    pub fn get_ref<Q: ?Sized + Eq + Hash>(self, key: &Q) -> Option<Ref<'a, V, S>>
+    where
+        K: Borrow<Q>,
+    {
+        unsafe {
+            S::_fork_all(
+                self,
+                |x| x.get(key).map(|x| Ref::new_unchecked(x)),
+                |x| x.get_mut(key).map(|x| Ref::new_unchecked_mut(x)),
+                |x| {
+                    let (mut x, slot) = x.into_slot();
+                    x.remove(key)
+                        .map(|x| Ref::new_unchecked_owned(slot.fill(x)))
+                },
+            )
+        }
+    }
+}
+
+impl<'a, K, V, H, S: RefType> Ref<'a, HashMap<K, V, H>, S> {
+    /// Iterates over the values of the map as Refs.
+    #[inline(always)]
+    pub fn values_ref(self) -> HashMapValues<'a, K, V, S> {
+        ValuesIter {
+            iter: ForkIter::new(self, |x| x.values(), |x| x.values_mut()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V, H, S: RefType> IntoIterator for Ref<'a, HashMap<K, V, H>, S> {
+    type Item = (&'a K, Ref<'a, V, S>);
+    type IntoIter = HashMapIter<'a, K, V, S>;
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        MapIter {
+            iter: ForkIter::new(self, |x| x.iter(), |x| x.iter_mut()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V, H> Ref<'a, HashMap<K, V, H>, Owned> {
+    /// Iterates over the values of the map as Owned refs, consuming the map.
+    #[inline(always)]
+    pub fn values_ref(self) -> HashMapOwnedValues<'a, V> {
+        Owned::new(self.into_inner().into_values().collect::<Vec<_>>()).into_iter()
+    }
+}
+
+impl<'a, K, V, H> IntoIterator for Ref<'a, HashMap<K, V, H>, Owned> {
+    type Item = (K, Ref<'a, V, Owned>);
+    type IntoIter = HashMapOwnedIter<'a, K, V>;
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        OwnedIter::new(self.into_inner().into_iter().collect()).map(owned_entry)
+    }
+}
+
+impl<'q, K, V, Q: ?Sized> IndexRef<&'q Q> for BTreeMap<K, V>
+where
+    K: Borrow<Q> + Ord,
+    Q: Ord,
+{
+    type Output = V;
+    /// # Panics
+    ///
+    /// Panics if the key is not in the map. Owned maps remove the value.
+    #[inline(always)]
+    fn index_ref<'a, S: AccessType>(this: Ref<'a, Self, S>, key: &'q Q) -> Ref<'a, V, S> {
+        this.get_ref(key).expect("key not found in map")
+    }
+}
+
+impl<'a, K: Ord, V, S: AccessType> Ref<'a, BTreeMap<K, V>, S> {
+    /// Projects onto the value of a key, like `get` and `get_mut`. Owned maps remove the value.
+    #[inline(always)]
+    pub fn get_ref<Q: ?Sized + Ord>(self, key: &Q) -> Option<Ref<'a, V, S>>
+    where
+        K: Borrow<Q>,
+    {
+        unsafe {
+            S::_fork_all(
+                self,
+                |x| x.get(key).map(|x| Ref::new_unchecked(x)),
+                |x| x.get_mut(key).map(|x| Ref::new_unchecked_mut(x)),
+                |x| {
+                    let (mut x, slot) = x.into_slot();
+                    x.remove(key)
+                        .map(|x| Ref::new_unchecked_owned(slot.fill(x)))
+                },
+            )
+        }
+    }
+}
+
+impl<'a, K, V, S: RefType> Ref<'a, BTreeMap<K, V>, S> {
+    /// Iterates over the values of the map in order of their keys as Refs.
+    #[inline(always)]
+    pub fn values_ref(self) -> BTreeMapValues<'a, K, V, S> {
+        ValuesIter {
+            iter: ForkIter::new(self, |x| x.values(), |x| x.values_mut()),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Iterates over the entries of the map whose keys are within a range, like `range` and `range_mut`.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as `BTreeMap::range`.
+    #[inline(always)]
+    pub fn range_ref<Q: ?Sized + Ord, R: RangeBounds<Q>>(
+        self,
+        range: R,
+    ) -> BTreeMapRange<'a, K, V, S>
+    where
+        K: Borrow<Q> + Ord,
+    {
+        let iter = match self.into_either() {
+            Either::Left(x) => ForkIter::Shared(x.range(range)),
+            Either::Right(x) => ForkIter::Unique(x.range_mut(range)),
+        };
+        MapIter {
+            iter,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V, S: RefType> IntoIterator for Ref<'a, BTreeMap<K, V>, S> {
+    type Item = (&'a K, Ref<'a, V, S>);
+    type IntoIter = BTreeMapIter<'a, K, V, S>;
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        MapIter {
+            iter: ForkIter::new(self, |x| x.iter(), |x| x.iter_mut()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V> Ref<'a, BTreeMap<K, V>, Owned> {
+    /// Iterates over the values of the map in order of their keys as Owned refs, consuming the map.
+    #[inline(always)]
+    pub fn values_ref(self) -> BTreeMapOwnedValues<'a, V> {
+        Owned::new(self.into_inner().into_values().collect::<Vec<_>>()).into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for Ref<'a, BTreeMap<K, V>, Owned> {
+    type Item = (K, Ref<'a, V, Owned>);
+    type IntoIter = BTreeMapOwnedIter<'a, K, V>;
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        OwnedIter::new(self.into_inner().into_iter().collect()).map(owned_entry)
+    }
+}
@@ -0,0 +1,78 @@
+use crate::*;
+
+impl<'a, T, S: AccessType> Ref<'a, Option<T>, S> {
+    /// Converts a Ref to an Option into an Option of a Ref, like `Option::as_ref` and `Option::as_mut`.
+    #[inline(always)]
+    pub fn as_option(self) -> Option<Ref<'a, T, S>> {
+        unsafe {
+            S::_fork_all(
+                self,
+                |x| x.as_ref().map(|x| Ref::new_unchecked(x)),
+                |x| x.as_mut().map(|x| Ref::new_unchecked_mut(x)),
+                |x| {
+                    let (x, slot) = x.into_slot();
+                    x.map(|x| Ref::new_unchecked_owned(slot.fill(x)))
+                },
+            )
+        }
+    }
+}
+
+impl<'a, T, E, S: AccessType> Ref<'a, Result<T, E>, S> {
+    /// Converts a Ref to a Result into a Result of Refs, like `Result::as_ref` and `Result::as_mut`.
+    #[inline(always)]
+    pub fn as_result(self) -> Result<Ref<'a, T, S>, Ref<'a, E, S>> {
+        unsafe {
+            S::_fork_all(
+                self,
+                |x| match x {
+                    Ok(x) => Ok(Ref::new_unchecked(x)),
+                    Err(x) => Err(Ref::new_unchecked(x)),
+                },
+                |x| match x {
+                    Ok(x) => Ok(Ref::new_unchecked_mut(x)),
+                    Err(x) => Err(Ref::new_unchecked_mut(x)),
+                },
+                |x| match x.into_slot() {
+                    (Ok(x), slot) => Ok(Ref::new_unchecked_owned(slot.fill(x))),
+                    (Err(x), slot) => Err(Ref::new_unchecked_owned(slot.fill(x))),
+                },
+            )
+        }
+    }
+
+    /// Projects onto the success value, if any.
+    ///
+    /// This is an associated function, like `Ref::map`, so that it does not hide `Result::ok`.
+    #[inline(always)]
+    pub fn ok(this: Self) -> Option<Ref<'a, T, S>> {
+        this.as_result().ok()
+    }
+
+    /// Projects onto the error value, if any.
+    ///
+    /// This is an associated function, like `Ref::map`, so that it does not hide `Result::err`.
+    #[inline(always)]
+    pub fn err(this: Self) -> Option<Ref<'a, E, S>> {
+        this.as_result().err()
+    }
+}
+
+impl<'a, T, E, S: AccessType> Ref<'a, Option<Result<T, E>>, S> {
+    /// Converts a Ref to an `Option<Result<T, E>>` into a `Result` of an `Option` of Refs, like `Option::transpose`.
+    ///
+    /// This is an associated function, like `Ref::map`, so that it does not hide `Option::transpose`.
+    #[inline(always)]
+    pub fn transpose(this: Self) -> Result<Option<Ref<'a, T, S>>, Ref<'a, E, S>> {
+        this.as_option().map(|x| x.as_result()).transpose()
+    }
+}
+
+impl<'a, T: 'a> IntoIteratorRef<'a> for Option<T> {
+    type Item = T;
+    type IntoIter<S: AccessType> = std::option::IntoIter<Ref<'a, T, S>>;
+    #[inline(always)]
+    fn into_iter_ref<S: AccessType>(this: Ref<'a, Self, S>) -> Self::IntoIter<S> {
+        this.as_option().into_iter()
+    }
+}
@@ -0,0 +1,435 @@
+use crate::*;
+use std::alloc::dealloc;
+use std::alloc::Layout;
+use std::iter::FusedIterator;
+use std::mem::ManuallyDrop;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Memory holding the values of Owned refs. It is freed when dropped, without dropping the values in it.
+struct RawAlloc {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl RawAlloc {
+    /// Takes over the memory of a box, without its value.
+    #[inline(always)]
+    unsafe fn from_box<T: ?Sized>(value: NonNull<T>) -> Self {
+        RawAlloc {
+            ptr: value.cast(),
+            layout: Layout::for_value(value.as_ref()),
+        }
+    }
+
+    /// Takes over the buffer of a vector, without its elements.
+    #[inline(always)]
+    fn from_vec<T>(vec: Vec<T>) -> (NonNull<T>, usize, Self) {
+        let mut vec = ManuallyDrop::new(vec);
+        let value = NonNull::new(vec.as_mut_ptr()).unwrap();
+        let alloc = RawAlloc {
+            ptr: value.cast(),
+            layout: Layout::array::<T>(vec.capacity()).unwrap(),
+        };
+        (value, vec.len(), alloc)
+    }
+}
+
+impl Drop for RawAlloc {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            unsafe { dealloc(self.ptr.as_ptr(), self.layout) }
+        }
+    }
+}
+
+// A RawAlloc is only memory, which may be freed from any thread.
+unsafe impl Send for RawAlloc {}
+unsafe impl Sync for RawAlloc {}
+
+/// The memory holding an Owned value which is not a box of its own.
+enum Storage {
+    /// Memory holding only this value.
+    Unique(RawAlloc),
+    /// Memory shared with the other Owned refs split from the same value, freed once they are all dropped.
+    Shared(Arc<RawAlloc>),
+}
+
+/// The memory shared by the Owned refs split from one value, such as the fields of a struct.
+///
+/// This is only public so that ref_clone_derive can use it.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct OwnedAlloc(Arc<RawAlloc>);
+
+impl OwnedAlloc {
+    /// UNSAFE. Do not use unless you know exactly what you are doing.
+    ///
+    /// # Safety
+    ///
+    /// `S` must be `Owned`. `value` must be in this allocation, and must not be owned by anything else.
+    #[inline(always)]
+    pub unsafe fn __own<'a, T, S: Access>(&self, value: &mut T) -> Ref<'a, T, S> {
+        Ref::new_unchecked_owned(OwnedBox {
+            value: NonNull::from(value),
+            alloc: Some((Storage::Shared(self.0.clone()), read_box::<T>)),
+        })
+    }
+}
+
+/// Moves a value out of its memory into a box of its own.
+type ReadBox<T> = unsafe fn(NonNull<T>) -> Box<T>;
+
+unsafe fn read_box<T>(value: NonNull<T>) -> Box<T> {
+    Box::new(value.as_ptr().read())
+}
+
+/// Owns the value of an Owned ref, and drops it when dropped.
+///
+/// Values split from a larger value, such as the elements of a vector or the fields of a struct, are left where they are
+/// rather than moved into boxes of their own, and share its memory.
+pub struct OwnedBox<T: ?Sized> {
+    pub(crate) value: NonNull<T>,
+    /// The memory holding the value, and how to move the value into a box, or `None` if the value is a box of its own.
+    alloc: Option<(Storage, ReadBox<T>)>,
+}
+
+impl<T: ?Sized> OwnedBox<T> {
+    #[inline(always)]
+    pub(crate) fn from_box(value: Box<T>) -> Self {
+        OwnedBox {
+            value: unsafe { NonNull::new_unchecked(Box::into_raw(value)) },
+            alloc: None,
+        }
+    }
+
+    /// Gives up the value without dropping it, keeping its memory.
+    #[inline(always)]
+    fn into_storage(self) -> (NonNull<T>, Storage) {
+        let this = ManuallyDrop::new(self);
+        let storage = match unsafe { std::ptr::read(&this.alloc) } {
+            Some((storage, _)) => storage,
+            None => Storage::Unique(unsafe { RawAlloc::from_box(this.value) }),
+        };
+        (this.value, storage)
+    }
+
+    /// Moves the value into a box. This only allocates if the value was split from a larger value.
+    #[inline(always)]
+    pub fn into_box(self) -> Box<T> {
+        let this = ManuallyDrop::new(self);
+        match unsafe { std::ptr::read(&this.alloc) } {
+            Some((_storage, read)) => unsafe { read(this.value) },
+            None => unsafe { Box::from_raw(this.value.as_ptr()) },
+        }
+    }
+}
+
+impl<T> OwnedBox<T> {
+    /// UNSAFE. Do not use unless you know exactly what you are doing.
+    ///
+    /// This is only public so that ref_clone_derive can call it. The value is to be split into its fields with
+    /// `OwnedAlloc::__own`.
+    ///
+    /// # Safety
+    ///
+    /// `T` must not implement `Drop`, and every part of the value which needs to be dropped must be either given to
+    /// `OwnedAlloc::__own` or moved out.
+    #[inline(always)]
+    pub unsafe fn __into_parts<'b>(self) -> (&'b mut T, OwnedAlloc) {
+        let (value, storage) = self.into_storage();
+        let alloc = match storage {
+            Storage::Unique(alloc) => Arc::new(alloc),
+            Storage::Shared(alloc) => alloc,
+        };
+        (&mut *value.as_ptr(), OwnedAlloc(alloc))
+    }
+
+    /// Moves the value out, leaving its memory to be reused.
+    #[inline(always)]
+    pub(crate) fn into_slot(self) -> (T, Slot) {
+        let (value, storage) = self.into_storage();
+        let slot = Slot {
+            ptr: value.cast(),
+            layout: Layout::new::<T>(),
+            storage,
+        };
+        (unsafe { value.as_ptr().read() }, slot)
+    }
+
+    /// Moves the value out without moving it into a box.
+    #[inline(always)]
+    pub(crate) fn into_inner(self) -> T {
+        self.into_slot().0
+    }
+
+    /// Takes one element of a vector, dropping the others and keeping the buffer as its memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[inline(always)]
+    pub(crate) fn from_vec_element(vec: Vec<T>, i: usize) -> Self {
+        assert!(i < vec.len(), "index out of bounds");
+        let (value, len, alloc) = RawAlloc::from_vec(vec);
+        unsafe {
+            let value = value.as_ptr();
+            std::ptr::drop_in_place(std::ptr::slice_from_raw_parts_mut(value, i));
+            std::ptr::drop_in_place(std::ptr::slice_from_raw_parts_mut(
+                value.add(i + 1),
+                len - i - 1,
+            ));
+            OwnedBox {
+                value: NonNull::new_unchecked(value.add(i)),
+                alloc: Some((Storage::Unique(alloc), read_box::<T>)),
+            }
+        }
+    }
+}
+
+impl<T, const N: usize> OwnedBox<[T; N]> {
+    #[inline(always)]
+    pub(crate) fn unsize(self) -> OwnedBox<[T]> {
+        /// Moves the elements of an array out of its memory into a box of their own.
+        unsafe fn read_slice<T, const N: usize>(value: NonNull<[T]>) -> Box<[T]> {
+            let array: Box<[T; N]> = read_box(value.cast());
+            array
+        }
+        let this = ManuallyDrop::new(self);
+        let value = NonNull::slice_from_raw_parts(this.value.cast::<T>(), N);
+        match unsafe { std::ptr::read(&this.alloc) } {
+            Some((storage, _)) => OwnedBox {
+                value,
+                alloc: Some((storage, read_slice::<T, N>)),
+            },
+            None => OwnedBox { value, alloc: None },
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for OwnedBox<T> {
+    type Target = T;
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for OwnedBox<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<T: ?Sized> Drop for OwnedBox<T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        match &self.alloc {
+            // The memory is freed when the storage is dropped afterwards.
+            Some(_) => unsafe { std::ptr::drop_in_place(self.value.as_ptr()) },
+            None => unsafe { drop(Box::from_raw(self.value.as_ptr())) },
+        }
+    }
+}
+
+/// The memory of an Owned value which has been moved out, which is reused for the value projected from it if it fits.
+pub(crate) struct Slot {
+    ptr: NonNull<u8>,
+    layout: Layout,
+    storage: Storage,
+}
+
+impl Slot {
+    #[inline(always)]
+    pub(crate) fn fill<U>(self, value: U) -> OwnedBox<U> {
+        let layout = Layout::new::<U>();
+        if layout.size() <= self.layout.size() && layout.align() <= self.layout.align() {
+            let ptr = self.ptr.cast::<U>();
+            unsafe { ptr.as_ptr().write(value) };
+            OwnedBox {
+                value: ptr,
+                alloc: Some((self.storage, read_box::<U>)),
+            }
+        } else {
+            OwnedBox::from_box(Box::new(value))
+        }
+    }
+}
+
+/// An iterator over the elements of a collection as Owned refs, which share its buffer rather than each being boxed.
+pub struct OwnedIter<T> {
+    value: NonNull<T>,
+    range: Range<usize>,
+    alloc: Arc<RawAlloc>,
+}
+
+impl<T> OwnedIter<T> {
+    #[inline(always)]
+    pub(crate) fn new(vec: Vec<T>) -> Self {
+        let (value, len, alloc) = RawAlloc::from_vec(vec);
+        OwnedIter {
+            value,
+            range: 0..len,
+            alloc: Arc::new(alloc),
+        }
+    }
+
+    #[inline(always)]
+    fn get(&self, i: usize) -> OwnedBox<T> {
+        OwnedBox {
+            value: unsafe { NonNull::new_unchecked(self.value.as_ptr().add(i)) },
+            alloc: Some((Storage::Shared(self.alloc.clone()), read_box::<T>)),
+        }
+    }
+}
+
+impl<T> Iterator for OwnedIter<T> {
+    type Item = OwnedBox<T>;
+    #[inline(always)]
+    fn next(&mut self) -> Option<OwnedBox<T>> {
+        self.range.next().map(|i| self.get(i))
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for OwnedIter<T> {
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<OwnedBox<T>> {
+        self.range.next_back().map(|i| self.get(i))
+    }
+}
+
+impl<T> ExactSizeIterator for OwnedIter<T> {}
+
+impl<T> FusedIterator for OwnedIter<T> {}
+
+impl<T> Drop for OwnedIter<T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe {
+            let rest = self.value.as_ptr().add(self.range.start);
+            std::ptr::drop_in_place(std::ptr::slice_from_raw_parts_mut(rest, self.range.len()));
+        }
+    }
+}
+
+unsafe impl<T: Send + ?Sized> Send for OwnedBox<T> {}
+unsafe impl<T: Sync + ?Sized> Sync for OwnedBox<T> {}
+
+unsafe impl<T: Send> Send for OwnedIter<T> {}
+unsafe impl<T: Sync> Sync for OwnedIter<T> {}
+
+impl Access for Owned {
+    type Owner<T: ?Sized> = OwnedBox<T>;
+
+    #[inline(always)]
+    unsafe fn _owner<T: ?Sized>(value: NonNull<T>) -> OwnedBox<T> {
+        OwnedBox { value, alloc: None }
+    }
+
+    #[inline(always)]
+    unsafe fn _from_owned<T: ?Sized>(x: OwnedBox<T>) -> OwnedBox<T> {
+        x
+    }
+
+    #[inline(always)]
+    fn _fork_any<'a, F1, F2, F3, F4, T: ?Sized, R>(
+        x: Ref<'a, T, Self>,
+        _shared: F1,
+        _unique: F2,
+        owned: F3,
+        _cell: F4,
+    ) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+        F3: FnOnce(OwnedBox<T>) -> R,
+        F4: FnOnce(NonNull<T>) -> R,
+    {
+        owned(x.owner)
+    }
+}
+
+impl AccessType for Owned {
+    #[inline(always)]
+    fn _apply<F1, F2, F3, A, B>(f: RefFn<F1, F2, A, B, F3>, x: Ref<A, Self>) -> Ref<B, Self>
+    where
+        F1: FnOnce(&A) -> &B,
+        F2: FnOnce(&mut A) -> &mut B,
+        F3: FnOnce(A) -> B,
+    {
+        let (x, slot) = x.owner.into_slot();
+        unsafe { Ref::new_unchecked_owned(slot.fill((f.apply_owned)(x))) }
+    }
+
+    #[inline(always)]
+    fn _fork_all<'a, F1, F2, F3, T: ?Sized, R>(
+        x: Ref<'a, T, Self>,
+        _shared: F1,
+        _unique: F2,
+        owned: F3,
+    ) -> R
+    where
+        F1: FnOnce(&'a T) -> R,
+        F2: FnOnce(&'a mut T) -> R,
+        F3: FnOnce(OwnedBox<T>) -> R,
+    {
+        owned(x.owner)
+    }
+}
+
+impl Owned {
+    /// Creates a new owned Ref from a value.
+    #[inline(always)]
+    pub fn new<'a, T>(t: T) -> Ref<'a, T, Owned> {
+        Owned::from_box(Box::new(t))
+    }
+
+    /// Creates a new owned Ref from a boxed value. This allows the value to be unsized.
+    #[inline(always)]
+    pub fn from_box<'a, T: ?Sized>(t: Box<T>) -> Ref<'a, T, Owned> {
+        unsafe { Ref::new_unchecked_owned(OwnedBox::from_box(t)) }
+    }
+}
+
+impl<'a, T: ?Sized> Ref<'a, T, Owned> {
+    /// Mutably borrows the value owned by the Ref.
+    #[allow(clippy::should_implement_trait)]
+    #[inline(always)]
+    pub fn as_mut(&mut self) -> &mut T {
+        unsafe { self.value.as_mut() }
+    }
+
+    /// Converts the Ref into a box containing the value.
+    ///
+    /// This allocates if the value was split from a larger value, such as by iterating over an Owned vector.
+    #[inline(always)]
+    pub fn into_box(self) -> Box<T> {
+        self.owner.into_box()
+    }
+
+    /// Converts the Ref into the value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T
+    where
+        T: Sized,
+    {
+        self.owner.into_inner()
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for Ref<'a, T, Owned> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        self.as_mut()
+    }
+}
+
+unsafe impl<'a, T: Send + ?Sized> Send for Ref<'a, T, Owned> {}
+unsafe impl<'a, T: Sync + ?Sized> Sync for Ref<'a, T, Owned> {}
@@ -0,0 +1,210 @@
+use crate::*;
+use std::fmt;
+use std::mem::ManuallyDrop;
+use std::mem::MaybeUninit;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// An owner whose target may be borrowed with the borrow type `S` for as long as it is alive, even if it is moved.
+///
+/// Owners which may be shared, such as `Rc` and `Arc`, are only `StableDeref<Shared>`.
+///
+/// # Safety
+///
+/// The pointer returned by `value_ptr` must stay valid for as long as the owner is alive, even if the owner is moved.
+/// If `S` is `Unique`, the owner must have exclusive access to its target, and the pointer must be derived from a unique borrow.
+pub unsafe trait StableDeref<S: RefType>: Deref {
+    fn value_ptr(&mut self) -> NonNull<Self::Target>;
+}
+
+/// An owner together with a Ref projected from it, such as a `Box<Config>` and a `Ref<Section, S>`.
+///
+/// It derefs to the projected value, and `reborrow` gives a Ref which borrows from the OwnedRef.
+/// Use `map`, `index_ref` or `map_ref` to project the value further while keeping the owner.
+///
+/// Mutable access needs a uniquely owned owner, so an `Rc` can not be used with `Unique`:
+///
+/// ```compile_fail
+/// # use ref_clone::*;
+/// # use std::rc::Rc;
+/// let x = OwnedRef::<_, _, Unique>::new(Rc::new(1));
+/// ```
+pub struct OwnedRef<O, T: ?Sized, S: RefType> {
+    value: NonNull<T>,
+    // Kept in a MaybeUninit so that moving the OwnedRef does not retag a Box owner, which would invalidate `value`.
+    owner: MaybeUninit<O>,
+    // Invariant in T, like a Ref.
+    ty: PhantomData<(O, S, *mut T)>,
+}
+
+impl<O: StableDeref<S>, S: RefType> OwnedRef<O, O::Target, S> {
+    #[inline(always)]
+    pub fn new(owner: O) -> Self {
+        // The pointer is only taken once the owner is in place, as moving a Box invalidates pointers to its value.
+        let mut owner = MaybeUninit::new(owner);
+        OwnedRef {
+            value: unsafe { owner.assume_init_mut() }.value_ptr(),
+            owner,
+            ty: PhantomData,
+        }
+    }
+}
+
+impl<O, T: ?Sized, S: RefType> OwnedRef<O, T, S> {
+    /// # Safety
+    ///
+    /// The Ref must not outlive the OwnedRef, and may not overlap with any other Ref to the value if `S` is `Unique`.
+    #[inline(always)]
+    unsafe fn get<'a>(&self) -> Ref<'a, T, S> {
+        Ref {
+            value: self.value,
+            owner: S::_owner(self.value),
+            ty: PhantomData,
+        }
+    }
+
+    /// Projects the value with a function on Refs, keeping the owner.
+    ///
+    /// This allows projecting through the wrappers generated by `#[RefAccessors]`, such as `|x| x.to_wrapped().field`.
+    #[inline(always)]
+    pub fn map_ref<U: ?Sized, F>(self, f: F) -> OwnedRef<O, U, S>
+    where
+        F: for<'a> FnOnce(Ref<'a, T, S>) -> Ref<'a, U, S>,
+    {
+        // If `f` panics, the owner is leaked rather than dropped while the Ref may still be alive.
+        let this = ManuallyDrop::new(self);
+        let value = f(unsafe { this.get() }).value;
+        OwnedRef {
+            value,
+            owner: unsafe { std::ptr::read(&this.owner) },
+            ty: PhantomData,
+        }
+    }
+
+    /// Applies a RefFn to the value, keeping the owner.
+    #[inline(always)]
+    pub fn map<F1, F2, U: ?Sized>(self, f: RefFn<F1, F2, T, U>) -> OwnedRef<O, U, S>
+    where
+        F1: FnOnce(&T) -> &U,
+        F2: FnOnce(&mut T) -> &mut U,
+    {
+        self.map_ref(|x| S::_apply_once(f, x))
+    }
+
+    /// Indexes into the value, keeping the owner.
+    #[inline(always)]
+    pub fn index_ref<Idx>(self, index: Idx) -> OwnedRef<O, T::Output, S>
+    where
+        T: IndexRef<Idx>,
+    {
+        self.map_ref(|x| T::index_ref(x, index))
+    }
+
+    /// Reborrows the value as a Ref for as long as the OwnedRef is borrowed.
+    #[inline(always)]
+    pub fn reborrow(&mut self) -> Ref<'_, T, S> {
+        unsafe { self.get() }
+    }
+
+    /// Borrows the value as a shared Ref.
+    #[inline(always)]
+    pub fn as_shared(&self) -> Ref<'_, T, Shared> {
+        Shared::new(&**self)
+    }
+
+    /// Releases the projection, returning the owner.
+    #[inline(always)]
+    pub fn into_owner(self) -> O {
+        let this = ManuallyDrop::new(self);
+        unsafe { this.owner.assume_init_read() }
+    }
+}
+
+impl<O, T: ?Sized, S: RefType> Drop for OwnedRef<O, T, S> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { self.owner.assume_init_drop() }
+    }
+}
+
+impl<O, T: ?Sized, S: RefType> Deref for OwnedRef<O, T, S> {
+    type Target = T;
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<O, T: ?Sized> DerefMut for OwnedRef<O, T, Unique> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<'b, O, T: ?Sized, S: RefType, Wrapped> RefAccessors<Wrapped> for &'b mut OwnedRef<O, T, S>
+where
+    Ref<'b, T, S>: RefAccessors<Wrapped>,
+{
+    #[inline(always)]
+    fn to_wrapped(self) -> Wrapped {
+        self.reborrow().to_wrapped()
+    }
+}
+
+impl<'b, O, T: ?Sized, S: RefType, Wrapped> RefAccessorsMut<'b, Wrapped> for OwnedRef<O, T, S>
+where
+    T: 'b,
+    Ref<'b, T, S>: RefAccessors<Wrapped>,
+{
+    #[inline(always)]
+    fn wrap(&'b mut self) -> Wrapped {
+        self.reborrow().to_wrapped()
+    }
+}
+
+impl<O, T: ?Sized + fmt::Debug, S: RefType> fmt::Debug for OwnedRef<O, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+unsafe impl<O: Send, T: ?Sized + Sync> Send for OwnedRef<O, T, Shared> {}
+unsafe impl<O: Sync, T: ?Sized + Sync> Sync for OwnedRef<O, T, Shared> {}
+unsafe impl<O: Send, T: ?Sized + Send> Send for OwnedRef<O, T, Unique> {}
+unsafe impl<O: Sync, T: ?Sized + Sync> Sync for OwnedRef<O, T, Unique> {}
+
+unsafe impl<T: ?Sized, S: RefType> StableDeref<S> for Box<T> {
+    #[inline(always)]
+    fn value_ptr(&mut self) -> NonNull<T> {
+        NonNull::from(&mut **self)
+    }
+}
+
+unsafe impl<T, S: RefType> StableDeref<S> for Vec<T> {
+    #[inline(always)]
+    fn value_ptr(&mut self) -> NonNull<[T]> {
+        NonNull::from(&mut **self)
+    }
+}
+
+unsafe impl<S: RefType> StableDeref<S> for String {
+    #[inline(always)]
+    fn value_ptr(&mut self) -> NonNull<str> {
+        NonNull::from(&mut **self)
+    }
+}
+
+unsafe impl<T: ?Sized> StableDeref<Shared> for Rc<T> {
+    #[inline(always)]
+    fn value_ptr(&mut self) -> NonNull<T> {
+        NonNull::from(&**self)
+    }
+}
+
+unsafe impl<T: ?Sized> StableDeref<Shared> for Arc<T> {
+    #[inline(always)]
+    fn value_ptr(&mut self) -> NonNull<T> {
+        NonNull::from(&**self)
+    }
+}
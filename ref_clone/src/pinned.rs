@@ -0,0 +1,69 @@
+use crate::*;
+use std::pin::Pin;
+
+impl Shared {
+    /// Creates a new pinned shared Ref from a pinned shared borrow.
+    #[inline(always)]
+    pub fn new_pinned<'a, T: ?Sized>(t: Pin<&'a T>) -> Pin<Ref<'a, T, Shared>> {
+        unsafe { Pin::new_unchecked(Shared::new(t.get_ref())) }
+    }
+}
+
+impl Unique {
+    /// Creates a new pinned unique Ref from a pinned unique borrow.
+    #[inline(always)]
+    pub fn new_pinned<'a, T: ?Sized>(t: Pin<&'a mut T>) -> Pin<Ref<'a, T, Unique>> {
+        unsafe { Pin::new_unchecked(Unique::new(t.get_unchecked_mut())) }
+    }
+}
+
+impl Owned {
+    /// Creates a new pinned owned Ref from a value. As the value is kept on the heap, it is never moved.
+    #[inline(always)]
+    pub fn new_pinned<'a, T>(t: T) -> Pin<Ref<'a, T, Owned>> {
+        unsafe { Pin::new_unchecked(Owned::new(t)) }
+    }
+}
+
+impl<'a, T: ?Sized, S: RefType> Ref<'a, T, S> {
+    /// Converts a pinned Ref into a pinned borrow for the whole lifetime of the Ref.
+    #[inline(always)]
+    pub fn into_pinned_ref(this: Pin<Self>) -> Pin<&'a T> {
+        unsafe { Pin::new_unchecked(Pin::into_inner_unchecked(this).into_ref()) }
+    }
+
+    /// Reborrows a pinned Ref, so that it can be projected without being consumed.
+    #[inline(always)]
+    pub fn reborrow_pinned<'b>(this: &'b mut Pin<Self>) -> Pin<Ref<'b, T, S>> {
+        // `Pin` is `repr(transparent)`, and the reborrow stays pinned.
+        unsafe {
+            let this = &mut *(this as *mut Pin<Self> as *mut Self);
+            Pin::new_unchecked(this.reborrow())
+        }
+    }
+}
+
+impl<'a, T: ?Sized> Ref<'a, T, Unique> {
+    /// Converts a pinned Ref into a pinned mutable borrow for the whole lifetime of the Ref.
+    #[inline(always)]
+    pub fn into_pinned_mut(this: Pin<Self>) -> Pin<&'a mut T> {
+        unsafe { Pin::new_unchecked(Pin::into_inner_unchecked(this).into_mut()) }
+    }
+}
+
+impl<'a, F1, F2, A: ?Sized, B: ?Sized + 'a> RefFn<F1, F2, A, B>
+where
+    F1: FnOnce(&A) -> &B,
+    F2: FnOnce(&mut A) -> &mut B,
+{
+    /// Applies the RefFn to a pinned Ref, keeping the result pinned.
+    ///
+    /// # Safety
+    ///
+    /// The projection must be structural: `apply_mut` may not move out of its argument,
+    /// and the result must stay pinned for as long as the argument is.
+    #[inline(always)]
+    pub unsafe fn ap_pinned<T: RefType>(self, x: Pin<Ref<'a, A, T>>) -> Pin<Ref<'a, B, T>> {
+        Pin::new_unchecked(T::_apply_once(self, Pin::into_inner_unchecked(x)))
+    }
+}
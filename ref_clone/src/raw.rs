@@ -0,0 +1,103 @@
+use crate::*;
+use std::fmt;
+
+/// A raw pointer with the type of the borrow as a type parameter.
+///
+/// A `RawRef<T, Shared>` is a `*const T` and a `RawRef<T, Unique>` is a `*mut T`.
+/// Unlike a `Ref`, it has no lifetime and may dangle or point to uninitialised memory, which makes it suited
+/// to FFI and unsafe data structures. Projections generated by `#[RefAccessors(raw)]` use `addr_of!`, so
+/// they never create an intermediate reference.
+///
+/// Turning a RawRef back into a `Ref` is `unsafe`, see `RawRef::to_ref`.
+pub struct RawRef<T: ?Sized, S: RefType> {
+    ptr: *mut T,
+    ty: PhantomData<S>,
+}
+
+impl Shared {
+    /// Creates a new shared RawRef from a const pointer.
+    #[inline(always)]
+    pub fn new_raw<T: ?Sized>(ptr: *const T) -> RawRef<T, Shared> {
+        RawRef::__new_raw(ptr as *mut T)
+    }
+}
+
+impl Unique {
+    /// Creates a new unique RawRef from a mut pointer.
+    #[inline(always)]
+    pub fn new_raw<T: ?Sized>(ptr: *mut T) -> RawRef<T, Unique> {
+        RawRef::__new_raw(ptr)
+    }
+}
+
+impl<T: ?Sized, S: RefType> RawRef<T, S> {
+    /// Creates a RawRef of any type from a pointer.
+    ///
+    /// Shared RawRefs are stored as a `*mut T` but are never written through.
+    /// This is used by ref_clone_derive.
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn __new_raw(ptr: *mut T) -> Self {
+        RawRef {
+            ptr,
+            ty: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn as_ptr(self) -> *const T {
+        self.ptr
+    }
+
+    /// Forgets that the RawRef may be written through.
+    #[inline(always)]
+    pub fn to_shared(self) -> RawRef<T, Shared> {
+        RawRef::__new_raw(self.ptr)
+    }
+
+    /// Converts the RawRef into a Ref.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be non-null, aligned and point to an initialised `T`,
+    /// and the value must follow the aliasing rules of `&'a T` or `&'a mut T` for `'a`.
+    #[inline(always)]
+    pub unsafe fn to_ref<'a>(self) -> Ref<'a, T, S> {
+        let value = NonNull::new_unchecked(self.ptr);
+        Ref {
+            value,
+            owner: S::_owner(value),
+            ty: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> RawRef<T, Unique> {
+    #[inline(always)]
+    pub fn as_mut_ptr(self) -> *mut T {
+        self.ptr
+    }
+}
+
+impl<'a, T: ?Sized, S: RefType> Ref<'a, T, S> {
+    /// Converts the Ref into a RawRef of the same type.
+    #[inline(always)]
+    pub fn into_raw(self) -> RawRef<T, S> {
+        RawRef::__new_raw(self.value.as_ptr())
+    }
+}
+
+impl<T: ?Sized, S: RefType> Clone for RawRef<T, S> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized, S: RefType> Copy for RawRef<T, S> {}
+
+impl<T: ?Sized, S: RefType> fmt::Debug for RawRef<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("RawRef").field(&self.ptr).finish()
+    }
+}
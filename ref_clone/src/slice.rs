@@ -0,0 +1,79 @@
+use crate::*;
+
+/// The two halves of a split slice.
+type Halves<'a, T, S> = (Ref<'a, [T], S>, Ref<'a, [T], S>);
+
+/// An element split off from a slice, and the rest of the slice.
+type ElementAndRest<'a, T, S> = (Ref<'a, T, S>, Ref<'a, [T], S>);
+
+// These are associated functions rather than methods so that they do not shadow the methods of the same names on slices.
+// Refs to arrays may be split by first converting them into Refs to slices with `into`.
+impl<'a, T, S: RefType> Ref<'a, [T], S> {
+    /// Divides the slice into two disjoint Refs at an index, like `split_at` and `split_at_mut`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid > len`.
+    #[inline(always)]
+    pub fn split_at(this: Self, mid: usize) -> Halves<'a, T, S> {
+        unsafe {
+            S::_fork(
+                this,
+                |x| {
+                    let (a, b) = x.split_at(mid);
+                    (Ref::new_unchecked(a), Ref::new_unchecked(b))
+                },
+                |x| {
+                    let (a, b) = x.split_at_mut(mid);
+                    (Ref::new_unchecked_mut(a), Ref::new_unchecked_mut(b))
+                },
+            )
+        }
+    }
+
+    /// Divides the slice into two disjoint Refs at an index, returning `None` if `mid > len`.
+    #[inline(always)]
+    pub fn split_at_checked(this: Self, mid: usize) -> Option<Halves<'a, T, S>> {
+        if mid <= this.len() {
+            Some(Ref::split_at(this, mid))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the first element and the rest of the slice, or `None` if it is empty.
+    #[inline(always)]
+    pub fn split_first(this: Self) -> Option<ElementAndRest<'a, T, S>> {
+        unsafe {
+            S::_fork(
+                this,
+                |x| {
+                    x.split_first()
+                        .map(|(a, b)| (Ref::new_unchecked(a), Ref::new_unchecked(b)))
+                },
+                |x| {
+                    x.split_first_mut()
+                        .map(|(a, b)| (Ref::new_unchecked_mut(a), Ref::new_unchecked_mut(b)))
+                },
+            )
+        }
+    }
+
+    /// Returns the last element and the rest of the slice, or `None` if it is empty.
+    #[inline(always)]
+    pub fn split_last(this: Self) -> Option<ElementAndRest<'a, T, S>> {
+        unsafe {
+            S::_fork(
+                this,
+                |x| {
+                    x.split_last()
+                        .map(|(a, b)| (Ref::new_unchecked(a), Ref::new_unchecked(b)))
+                },
+                |x| {
+                    x.split_last_mut()
+                        .map(|(a, b)| (Ref::new_unchecked_mut(a), Ref::new_unchecked_mut(b)))
+                },
+            )
+        }
+    }
+}
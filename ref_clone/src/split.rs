@@ -0,0 +1,85 @@
+use crate::*;
+
+/// A tuple of shared borrows, such as `(&'a A, &'a B)`, which `Ref::map_split_n` converts into a tuple of Refs.
+///
+/// This is implemented for tuples of 2 to 12 borrows.
+pub trait SplitBorrows<'a>: private::Sealed {
+    /// The same tuple with unique borrows, such as `(&'a mut A, &'a mut B)`.
+    type Unique;
+    /// The tuple of Refs, such as `(Ref<'a, A, S>, Ref<'a, B, S>)`.
+    type Refs<S: RefType>;
+
+    #[doc(hidden)]
+    unsafe fn _from_shared<S: RefType>(x: Self) -> Self::Refs<S>;
+
+    #[doc(hidden)]
+    unsafe fn _from_unique<S: RefType>(x: Self::Unique) -> Self::Refs<S>;
+}
+
+macro_rules! split_borrows {
+    ($($t:ident $x:ident),*) => {
+        // Tuples are sealed in the tuple module.
+        impl<'a, $($t: ?Sized),*> SplitBorrows<'a> for ($(&'a $t,)*) {
+            type Unique = ($(&'a mut $t,)*);
+            type Refs<S: RefType> = ($(Ref<'a, $t, S>,)*);
+
+            #[inline(always)]
+            unsafe fn _from_shared<S: RefType>(($($x,)*): Self) -> Self::Refs<S> {
+                ($(Ref::new_unchecked($x),)*)
+            }
+
+            #[inline(always)]
+            unsafe fn _from_unique<S: RefType>(($($x,)*): Self::Unique) -> Self::Refs<S> {
+                ($(Ref::new_unchecked_mut($x),)*)
+            }
+        }
+    };
+}
+
+split_borrows!(A a, B b);
+split_borrows!(A a, B b, C c);
+split_borrows!(A a, B b, C c, D d);
+split_borrows!(A a, B b, C c, D d, E e);
+split_borrows!(A a, B b, C c, D d, E e, F f);
+split_borrows!(A a, B b, C c, D d, E e, F f, G g);
+split_borrows!(A a, B b, C c, D d, E e, F f, G g, H h);
+split_borrows!(A a, B b, C c, D d, E e, F f, G g, H h, I i);
+split_borrows!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j);
+split_borrows!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k);
+split_borrows!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l);
+
+impl<'a, T: ?Sized, S: RefType> Ref<'a, T, S> {
+    /// Splits the Ref into two Refs to disjoint parts of the value, like `std::cell::RefMut::map_split`.
+    ///
+    /// `f` is used if the Ref is Shared, and `f_mut` if it is Unique.
+    #[inline(always)]
+    pub fn map_split<U: ?Sized, V: ?Sized, F1, F2>(
+        this: Self,
+        f: F1,
+        f_mut: F2,
+    ) -> (Ref<'a, U, S>, Ref<'a, V, S>)
+    where
+        F1: FnOnce(&'a T) -> (&'a U, &'a V),
+        F2: FnOnce(&'a mut T) -> (&'a mut U, &'a mut V),
+    {
+        Ref::map_split_n(this, f, f_mut)
+    }
+
+    /// Splits the Ref into a tuple of Refs to disjoint parts of the value, such as `(Ref<'a, A, S>, Ref<'a, B, S>, Ref<'a, C, S>)`.
+    ///
+    /// `f` returns a tuple of shared borrows and `f_mut` the same tuple of unique borrows.
+    #[inline(always)]
+    pub fn map_split_n<P: SplitBorrows<'a>, F1, F2>(this: Self, f: F1, f_mut: F2) -> P::Refs<S>
+    where
+        F1: FnOnce(&'a T) -> P,
+        F2: FnOnce(&'a mut T) -> P::Unique,
+    {
+        unsafe {
+            S::_fork(
+                this,
+                |x| P::_from_shared(f(x)),
+                |x| P::_from_unique(f_mut(x)),
+            )
+        }
+    }
+}
@@ -3,13 +3,14 @@ use crate::*;
 pub trait DerefRef {
     type Target: ?Sized;
 
-    fn deref_ref<'a, S: RefType>(self: Ref<'a, Self, S>) -> Ref<'a, Self::Target, S>;
+    fn deref_ref<'a, S: RefType>(this: Ref<'a, Self, S>) -> Ref<'a, Self::Target, S>;
 }
 
 pub trait IndexRef<Idx> {
     type Output: ?Sized;
 
-    fn index_ref<'a, S: RefType>(self: Ref<'a, Self, S>, index: Idx) -> Ref<'a, Self::Output, S>;
+    fn index_ref<'a, S: AccessType>(this: Ref<'a, Self, S>, index: Idx)
+        -> Ref<'a, Self::Output, S>;
 }
 
 #[repr(transparent)]
@@ -21,7 +22,12 @@ impl<'a, S: Iterator<Item = Ref<'a, I, Shared>>, I> Iterator for RefIterator<'a,
     type Item = &'a I;
     #[inline(always)]
     fn next(&mut self) -> Option<&'a I> {
-        self.0.next().map(|x| x.as_ref())
+        self.0.next().map(|x| x.into_ref())
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
     }
 }
 
@@ -29,25 +35,91 @@ impl<'a, S: Iterator<Item = Ref<'a, I, Unique>>, I> Iterator for RefIterator<'a,
     type Item = &'a mut I;
     #[inline(always)]
     fn next(&mut self) -> Option<&'a mut I> {
-        self.0.next().map(|mut x| x.as_mut())
+        self.0.next().map(|x| x.into_mut())
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
     }
 }
 
+impl<'a, S: DoubleEndedIterator<Item = Ref<'a, I, Shared>>, I> DoubleEndedIterator
+    for RefIterator<'a, Shared, S, I>
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<&'a I> {
+        self.0.next_back().map(|x| x.into_ref())
+    }
+}
+
+impl<'a, S: DoubleEndedIterator<Item = Ref<'a, I, Unique>>, I> DoubleEndedIterator
+    for RefIterator<'a, Unique, S, I>
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<&'a mut I> {
+        self.0.next_back().map(|x| x.into_mut())
+    }
+}
+
+impl<'a, S: ExactSizeIterator<Item = Ref<'a, I, Shared>>, I> ExactSizeIterator
+    for RefIterator<'a, Shared, S, I>
+{
+}
+
+impl<'a, S: ExactSizeIterator<Item = Ref<'a, I, Unique>>, I> ExactSizeIterator
+    for RefIterator<'a, Unique, S, I>
+{
+}
+
 pub trait IntoIteratorRef<'a> {
     type Item: 'a;
-    type IntoIter<T: RefType>: Iterator<Item = Ref<'a, Self::Item, T>>;
-    fn into_iter_ref<T: RefType>(self: Ref<'a, Self, T>) -> Self::IntoIter<T>;
+    type IntoIter<T: AccessType>: Iterator<Item = Ref<'a, Self::Item, T>>
+    where
+        Self: 'a;
+    fn into_iter_ref<T: AccessType>(this: Ref<'a, Self, T>) -> Self::IntoIter<T>;
     #[inline(always)]
     fn iter(&'a self) -> RefIterator<'a, Shared, Self::IntoIter<Shared>, Self::Item> {
-        RefIterator(Ref::new(self).into_iter_ref())
+        RefIterator(Self::into_iter_ref(Ref::new(self)))
     }
     #[inline(always)]
     fn iter_mut(&'a mut self) -> RefIterator<'a, Unique, Self::IntoIter<Unique>, Self::Item> {
-        RefIterator(Ref::new(self).into_iter_ref())
+        RefIterator(Self::into_iter_ref(Ref::new(self)))
+    }
+}
+
+/// Method syntax for the projection traits. These forward to the associated functions, so they work on stable.
+impl<'a, T: ?Sized, S: RefType> Ref<'a, T, S> {
+    #[inline(always)]
+    pub fn deref_ref(self) -> Ref<'a, T::Target, S>
+    where
+        T: DerefRef,
+    {
+        T::deref_ref(self)
+    }
+}
+
+impl<'a, T: ?Sized, S: AccessType> Ref<'a, T, S> {
+    #[inline(always)]
+    pub fn index_ref<Idx>(self, index: Idx) -> Ref<'a, T::Output, S>
+    where
+        T: IndexRef<Idx>,
+    {
+        T::index_ref(self, index)
+    }
+
+    #[inline(always)]
+    pub fn into_iter_ref(self) -> T::IntoIter<S>
+    where
+        T: IntoIteratorRef<'a>,
+    {
+        T::into_iter_ref(self)
     }
 }
 
-impl<'a, S, T: RefType> IntoIterator for Ref<'a, S, T>
+// Because of this impl, a `Ref<'a, I, Unique>` can not itself be an `Iterator`, as it would overlap with the
+// blanket `IntoIterator` impl for iterators. Use `as_mut` or `into_mut` to iterate through a `&mut I` instead.
+impl<'a, S: ?Sized, T: AccessType> IntoIterator for Ref<'a, S, T>
 where
     S: IntoIteratorRef<'a>,
 {
@@ -55,6 +127,6 @@ where
     type IntoIter = S::IntoIter<T>;
     #[inline(always)]
     fn into_iter(self) -> S::IntoIter<T> {
-        self.into_iter_ref()
+        S::into_iter_ref(self)
     }
 }
@@ -0,0 +1,141 @@
+use crate::*;
+use std::convert::Infallible;
+
+/// The kind of output of a fallible projection, named after the type of its failure.
+///
+/// `Option<Infallible>` is the residual of `Option<T>`, and `Result<Infallible, E>` is the residual of `Result<T, E>`.
+pub trait Residual: private::Sealed {
+    type Output<T>;
+
+    fn map<T, U>(x: Self::Output<T>, f: impl FnOnce(T) -> U) -> Self::Output<U>;
+
+    fn and_then<T, U>(x: Self::Output<T>, f: impl FnOnce(T) -> Self::Output<U>) -> Self::Output<U>;
+}
+
+impl Residual for Option<Infallible> {
+    type Output<T> = Option<T>;
+
+    #[inline(always)]
+    fn map<T, U>(x: Option<T>, f: impl FnOnce(T) -> U) -> Option<U> {
+        x.map(f)
+    }
+
+    #[inline(always)]
+    fn and_then<T, U>(x: Option<T>, f: impl FnOnce(T) -> Option<U>) -> Option<U> {
+        x.and_then(f)
+    }
+}
+
+impl<E> Residual for Result<Infallible, E> {
+    type Output<T> = Result<T, E>;
+
+    #[inline(always)]
+    fn map<T, U>(x: Result<T, E>, f: impl FnOnce(T) -> U) -> Result<U, E> {
+        x.map(f)
+    }
+
+    #[inline(always)]
+    fn and_then<T, U>(x: Result<T, E>, f: impl FnOnce(T) -> Result<U, E>) -> Result<U, E> {
+        x.and_then(f)
+    }
+}
+
+/// A fallible RefFn, for projections such as optional children, checked indexing and enum variants.
+///
+/// `apply` and `apply_mut` return an `Option` when created with `TryRefFn::new`, and a `Result` when created with `TryRefFn::new_result`.
+pub struct TryRefFn<F1, F2, A: ?Sized, B: ?Sized, R: Residual = Option<Infallible>> {
+    pub apply: F1,
+    pub apply_mut: F2,
+    _marker: PhantomData<(*const A, *const B, R)>,
+}
+
+impl<F1, F2, A: ?Sized, B: ?Sized> TryRefFn<F1, F2, A, B>
+where
+    F1: FnOnce(&A) -> Option<&B>,
+    F2: FnOnce(&mut A) -> Option<&mut B>,
+{
+    #[inline(always)]
+    pub fn new(apply: F1, apply_mut: F2) -> Self {
+        Self::from_fns(apply, apply_mut)
+    }
+}
+
+impl<F1, F2, A: ?Sized, B: ?Sized, E> TryRefFn<F1, F2, A, B, Result<Infallible, E>>
+where
+    F1: FnOnce(&A) -> Result<&B, E>,
+    F2: FnOnce(&mut A) -> Result<&mut B, E>,
+{
+    #[inline(always)]
+    pub fn new_result(apply: F1, apply_mut: F2) -> Self {
+        Self::from_fns(apply, apply_mut)
+    }
+}
+
+impl<F1, F2, A: ?Sized, B: ?Sized, R: Residual> TryRefFn<F1, F2, A, B, R>
+where
+    F1: FnOnce(&A) -> R::Output<&B>,
+    F2: FnOnce(&mut A) -> R::Output<&mut B>,
+{
+    #[inline(always)]
+    fn from_fns(apply: F1, apply_mut: F2) -> Self {
+        TryRefFn {
+            apply,
+            apply_mut,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Like RefFn, applying a TryRefFn only needs it to work for borrows of lifetime `'a`.
+impl<'a, F1, F2, A: ?Sized + 'a, B: ?Sized + 'a, R: Residual> TryRefFn<F1, F2, A, B, R>
+where
+    F1: FnOnce(&'a A) -> R::Output<&'a B>,
+    F2: FnOnce(&'a mut A) -> R::Output<&'a mut B>,
+{
+    /// Applies the TryRefFn, returning `None` or `Err` if the projection failed.
+    #[inline(always)]
+    pub fn ap<T: RefType>(self, x: Ref<'a, A, T>) -> R::Output<Ref<'a, B, T>> {
+        let TryRefFn {
+            apply, apply_mut, ..
+        } = self;
+        T::_fork(
+            x,
+            |x| R::map(apply(x), |x| unsafe { Ref::new_unchecked(x) }),
+            |x| R::map(apply_mut(x), |x| unsafe { Ref::new_unchecked_mut(x) }),
+        )
+    }
+
+    /// Composes two TryRefFns, applying `self` and then `other` if `self` succeeded.
+    ///
+    /// Like `RefFn::then`, the intermediate type `B` need only outlive the Ref the composed TryRefFn is applied to.
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    pub fn and_then<G1, G2, C: ?Sized + 'a>(
+        self,
+        other: TryRefFn<G1, G2, B, C, R>,
+    ) -> TryRefFn<
+        impl FnOnce(&'a A) -> R::Output<&'a C>,
+        impl FnOnce(&'a mut A) -> R::Output<&'a mut C>,
+        A,
+        C,
+        R,
+    >
+    where
+        G1: FnOnce(&'a B) -> R::Output<&'a C>,
+        G2: FnOnce(&'a mut B) -> R::Output<&'a mut C>,
+    {
+        let TryRefFn {
+            apply, apply_mut, ..
+        } = self;
+        let TryRefFn {
+            apply: then,
+            apply_mut: then_mut,
+            ..
+        } = other;
+        TryRefFn {
+            apply: move |x| R::and_then(apply(x), then),
+            apply_mut: move |x| R::and_then(apply_mut(x), then_mut),
+            _marker: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::*;
+
+/// A tuple of 1 to 12 elements, which `Ref::split_tuple` destructures into a tuple of Refs.
+pub trait Tuple: private::Sealed {
+    /// The tuple of Refs, such as `(Ref<'a, A, S>, Ref<'a, B, S>)`.
+    type Refs<'a, S: AccessType>
+    where
+        Self: 'a;
+
+    #[doc(hidden)]
+    fn _split<S: AccessType>(this: Ref<'_, Self, S>) -> Self::Refs<'_, S>;
+}
+
+/// Replaces a token with a type or an expression, to repeat it once for each element of a tuple.
+macro_rules! replace {
+    ($x:tt $($y:tt)*) => {
+        $($y)*
+    };
+}
+
+macro_rules! tuple {
+    ($($t:ident $x:ident $i:tt),*) => {
+        impl<$($t),*> private::Sealed for ($($t,)*) {}
+
+        impl<$($t),*> Tuple for ($($t,)*) {
+            type Refs<'a, S: AccessType> = ($(Ref<'a, $t, S>,)*) where Self: 'a;
+
+            #[inline(always)]
+            fn _split<S: AccessType>(this: Ref<'_, Self, S>) -> Self::Refs<'_, S> {
+                unsafe {
+                    S::_fork_all(
+                        this,
+                        |x| ($(Ref::new_unchecked(&x.$i),)*),
+                        |x| ($(Ref::new_unchecked_mut(&mut x.$i),)*),
+                        |x| {
+                            let (x, alloc) = x.__into_parts();
+                            ($(alloc.__own(&mut x.$i),)*)
+                        },
+                    )
+                }
+            }
+        }
+
+        /// A tuple whose elements are all of the same type may be iterated like an array.
+        impl<'a, T: 'a> IntoIteratorRef<'a> for ($(replace!($t T),)*) {
+            type Item = T;
+            type IntoIter<S: AccessType> = std::array::IntoIter<Ref<'a, T, S>, { 0 $(+ replace!($t 1))* }>;
+            #[inline(always)]
+            fn into_iter_ref<S: AccessType>(this: Ref<'a, Self, S>) -> Self::IntoIter<S> {
+                let ($($x,)*) = this.split_tuple();
+                IntoIterator::into_iter([$($x),*])
+            }
+        }
+    };
+}
+
+tuple!(A a 0);
+tuple!(A a 0, B b 1);
+tuple!(A a 0, B b 1, C c 2);
+tuple!(A a 0, B b 1, C c 2, D d 3);
+tuple!(A a 0, B b 1, C c 2, D d 3, E e 4);
+tuple!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5);
+tuple!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6);
+tuple!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6, H h 7);
+tuple!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6, H h 7, I i 8);
+tuple!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6, H h 7, I i 8, J j 9);
+tuple!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6, H h 7, I i 8, J j 9, K k 10);
+tuple!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6, H h 7, I i 8, J j 9, K k 10, L l 11);
+
+impl<'a, T: Tuple, S: AccessType> Ref<'a, T, S> {
+    /// Destructures a Ref to a tuple into a tuple of Refs to its elements.
+    ///
+    /// This may also be used as a function, such as `iter.map(Ref::split_tuple)`.
+    #[inline(always)]
+    pub fn split_tuple(self) -> T::Refs<'a, S> {
+        T::_split(self)
+    }
+}
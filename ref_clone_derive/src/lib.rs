@@ -3,6 +3,21 @@
 //! To use this, prepend a `#[RefAccessors]` to a struct or enum definition.
 //! The macro automatically generates a wrapper type for the struct or enum which wraps all values in a `Ref`.
 //! This wrapper type may be accessed using the `to_wrapped` method on the trait `RefAccessors`.
+//! It may also be accessed without consuming the `Ref` using the `wrap` method on the trait `RefAccessorsMut`.
+//!
+//! By default the wrapper may only be created from borrows, and for structs also from `CellShared` refs. Use `#[RefAccessors(owned)]` to also allow creating it from `Owned` refs.
+//! This splits the value into its fields in place, and as such can not be used on types which implement `Drop`.
+//!
+//! `#[RefAccessors(pin)]` also generates a pinned wrapper, accessed using `to_wrapped_pinned` on a `Pin<Ref<..>>`.
+//! Fields are structurally pinned and become `Pin<Ref<..>>`s, unless they are marked with `#[unpin]`, in which case they are plain `Ref`s.
+//! The type is then only `Unpin` when all of its pinned fields are, and may not implement `Drop`.
+//!
+//! `#[RefAccessors(raw)]` also generates a wrapper of `RawRef`s, accessed using the unsafe `to_wrapped_raw` on a `RawRef`.
+//! The fields are projected using `addr_of!`, so no intermediate reference is created. This is only supported for structs.
+//!
+//! The wrapper has a `downgrade` method, which weakens the type of the borrow of every field, such as from `Unique` to `Shared`.
+//!
+//! For structs, a `RefLens` is also generated for every field, created with `lens_` followed by the name of the field, such as `Foo::lens_x()`.
 
 use proc_macro::TokenStream;
 use proc_macro2::Span;
@@ -24,19 +39,83 @@ use token::Lt;
 
 #[proc_macro_attribute]
 #[allow(non_snake_case)]
-pub fn RefAccessors(_attr: TokenStream, mut input: TokenStream) -> TokenStream {
-    let ast = syn::parse(input.clone()).unwrap();
-    let out = impl_ref_accessors(&ast);
-    input.extend::<TokenStream>(out.into());
-    input
+pub fn RefAccessors(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let mut ast = syn::parse(input).unwrap();
+    let options = parse_options(attr.into());
+    let out = impl_ref_accessors(&ast, &options);
+    strip_unpin(&mut ast);
+    (quote! {
+        #ast
+        #out
+    })
+    .into()
+}
+
+/// The options given to `#[RefAccessors(...)]`.
+struct Options {
+    /// Whether the wrapper may also be created from `Owned` refs.
+    owned: bool,
+    /// Whether a pinned wrapper is also generated.
+    pin: bool,
+    /// Whether a wrapper of raw pointers is also generated.
+    raw: bool,
+}
+
+fn parse_options(attr: proc_macro2::TokenStream) -> Options {
+    let mut options = Options {
+        owned: false,
+        pin: false,
+        raw: false,
+    };
+    let idents = Punctuated::<Ident, Token!(,)>::parse_terminated
+        .parse2(attr)
+        .unwrap();
+    for ident in idents {
+        match &ident.to_string()[..] {
+            "owned" => options.owned = true,
+            "pin" => options.pin = true,
+            "raw" => options.raw = true,
+            x => panic!("Unknown option for RefAccessors: {}", x),
+        }
+    }
+    options
+}
+
+fn is_unpin(field: &Field) -> bool {
+    field.attrs.iter().any(|x| x.path.is_ident("unpin"))
+}
+
+fn all_fields(data: &Data) -> Vec<&Field> {
+    match data {
+        Struct(DataStruct { fields, .. }) => fields.iter().collect(),
+        Enum(DataEnum { variants, .. }) => variants.iter().flat_map(|x| x.fields.iter()).collect(),
+        _ => vec![],
+    }
+}
+
+/// Removes the `#[unpin]` attributes, which are only markers for `RefAccessors`.
+fn strip_unpin(ast: &mut DeriveInput) {
+    let strip = |fields: &mut Fields| {
+        for field in fields.iter_mut() {
+            field.attrs.retain(|x| !x.path.is_ident("unpin"));
+        }
+    };
+    match &mut ast.data {
+        Struct(DataStruct { fields, .. }) => strip(fields),
+        Enum(DataEnum { variants, .. }) => variants.iter_mut().for_each(|x| strip(&mut x.fields)),
+        _ => {}
+    }
 }
 
 /// First TokenStream is the Struct definition (without the outside wrapper). Second TokenStream is the generator of it.
+///
+/// `new` is the function used to wrap each field, and must accept the borrows produced by the match.
 fn gen_named(
     ast: &FieldsNamed,
     ref_path: &impl ToTokens,
     lt: &Lifetime,
     ref_type: &Ident,
+    new: &proc_macro2::TokenStream,
 ) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
     let interior = ast.named.iter().map(|x| {
         let Field { vis, ident, ty, .. } = x;
@@ -56,7 +135,7 @@ fn gen_named(
 
         quote! {
             #ident : {
-                unsafe { ::ref_clone::Ref::__new_unsafe(#ident) }
+                unsafe { #new(#ident) }
             },
         }
     });
@@ -81,6 +160,7 @@ fn gen_unnamed(
     ref_path: &impl ToTokens,
     lt: &Lifetime,
     ref_type: &Ident,
+    new: &proc_macro2::TokenStream,
 ) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
     let interior = ast.unnamed.iter().map(|x| {
         let Field { vis, ty, .. } = x;
@@ -94,7 +174,7 @@ fn gen_unnamed(
     let interior_gen = ast.unnamed.iter().enumerate().map(|(i, _)| {
         let ident = format_ident!("_{}", i);
         quote! {
-            unsafe { ::ref_clone::Ref::__new_unsafe(#ident) }
+            unsafe { #new(#ident) }
         }
     });
     let match_gen = ast.unnamed.iter().enumerate().map(|(i, _)| {
@@ -104,7 +184,7 @@ fn gen_unnamed(
         }
     });
     let struct_gen = quote! {
-        (#(#match_gen)*) => #ref_path(#(#interior_gen)*)
+        (#(#match_gen)*) => #ref_path(#(#interior_gen),*)
     };
     (struct_def, struct_gen)
 }
@@ -114,10 +194,11 @@ fn gen(
     ref_path: &impl ToTokens,
     lt: &Lifetime,
     ref_type: &Ident,
+    new: &proc_macro2::TokenStream,
 ) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
     match ast {
-        Named(data) => gen_named(data, ref_path, lt, ref_type),
-        Unnamed(data) => gen_unnamed(data, ref_path, lt, ref_type),
+        Named(data) => gen_named(data, ref_path, lt, ref_type, new),
+        Unnamed(data) => gen_unnamed(data, ref_path, lt, ref_type, new),
         _ => panic!("Panic in function Gen: Unit types are not supported for RefAccessors."),
     }
 }
@@ -128,22 +209,23 @@ struct RefGenerics<'a> {
     lt: Lifetime,
     generics: Generics,
     ref_types: TypeGenerics<'a>,
+    wrap_lt: Lifetime,
+    /// Generics of the `RefAccessorsMut` impl, which has an extra lifetime for the reborrow.
+    wrap_generics: Generics,
+    /// Generics of the wrapper type returned by `wrap`, which uses the reborrow lifetime.
+    wrapped_generics: Generics,
+    pin_path: Ident,
+    /// Generics of the pinned wrapper, which may only be created from borrows.
+    pin_generics: Generics,
 }
 
-fn compute_generics<'a>(name: &'a Ident, start: &'a Generics) -> RefGenerics<'a> {
-    // Names are inverted. Names which are uppercase are identifiers while lowercase names are types.
-    // (Yes, I know this is bad, but it's to avoid name collisions by disregarding every single naming convention.)
-    let lt = Lifetime::new(&format!("'__Ref__Access__{}", name)[..], Span::call_site());
-    let ref_type = format_ident!("__{}__ref_type", name);
-    let ref_path = format_ident!("{}Ref", name);
-    let generics = Punctuated::<syn::GenericParam, Token!(,)>::parse_terminated
-        .parse2(quote! {
-            #lt, #ref_type : ::ref_clone::RefType
-        })
+fn extend_generics(start: &Generics, extra: proc_macro2::TokenStream) -> Generics {
+    let extra = Punctuated::<syn::GenericParam, Token!(,)>::parse_terminated
+        .parse2(extra)
         .unwrap();
     let mut clone = start.params.clone();
-    clone.extend(generics);
-    let generics = Generics {
+    clone.extend(extra);
+    Generics {
         lt_token: Some(Lt {
             spans: [Span::call_site()],
         }),
@@ -152,7 +234,46 @@ fn compute_generics<'a>(name: &'a Ident, start: &'a Generics) -> RefGenerics<'a>
         }),
         params: clone,
         where_clause: start.where_clause.clone(),
-    };
+    }
+}
+
+/// `access_type` is the bound on the type of access of the wrapper.
+fn compute_generics<'a>(
+    name: &'a Ident,
+    start: &'a Generics,
+    access_type: proc_macro2::TokenStream,
+) -> RefGenerics<'a> {
+    // Names are inverted. Names which are uppercase are identifiers while lowercase names are types.
+    // (Yes, I know this is bad, but it's to avoid name collisions by disregarding every single naming convention.)
+    let lt = Lifetime::new(&format!("'__Ref__Access__{}", name)[..], Span::call_site());
+    let ref_type = format_ident!("__{}__ref_type", name);
+    let wrap_lt = Lifetime::new(&format!("'__Ref__Wrap__{}", name)[..], Span::call_site());
+    let ref_path = format_ident!("{}Ref", name);
+    let generics = extend_generics(
+        start,
+        quote! {
+            #lt, #ref_type : #access_type
+        },
+    );
+    let wrap_generics = extend_generics(
+        start,
+        quote! {
+            #wrap_lt, #lt : #wrap_lt, #ref_type : ::ref_clone::RefType
+        },
+    );
+    let wrapped_generics = extend_generics(
+        start,
+        quote! {
+            #wrap_lt, #ref_type : ::ref_clone::RefType
+        },
+    );
+    let pin_path = format_ident!("{}PinRef", name);
+    let pin_generics = extend_generics(
+        start,
+        quote! {
+            #lt, #ref_type : ::ref_clone::RefType
+        },
+    );
     let ref_types = start.split_for_impl().1;
     RefGenerics {
         ref_path,
@@ -160,83 +281,599 @@ fn compute_generics<'a>(name: &'a Ident, start: &'a Generics) -> RefGenerics<'a>
         lt,
         generics,
         ref_types,
+        wrap_lt,
+        wrap_generics,
+        wrapped_generics,
+        pin_path,
+        pin_generics,
     }
 }
 
-fn impl_ref_accessors(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
+fn impl_ref_accessors(ast: &syn::DeriveInput, options: &Options) -> proc_macro2::TokenStream {
     let name = &ast.ident;
     let vis = &ast.vis;
 
+    // Only the fields of structs may be projected through a Cell, as the variant of an enum could be changed.
+    let access_type = match (&ast.data, options.owned) {
+        (Struct(_), false) => quote! { ::ref_clone::FieldType },
+        (Struct(_), true) => quote! { ::ref_clone::Access },
+        (_, false) => quote! { ::ref_clone::RefType },
+        (_, true) => quote! { ::ref_clone::AccessType },
+    };
+
     let RefGenerics {
         ref_path,
         ref_type,
         lt,
         generics,
         ref_types,
-    } = compute_generics(name, &ast.generics);
+        wrap_lt,
+        wrap_generics,
+        wrapped_generics,
+        pin_path,
+        pin_generics,
+    } = compute_generics(name, &ast.generics, access_type);
+
+    let pin_impl = if options.pin {
+        impl_pin_accessors(ast, &ref_path, &pin_path, &pin_generics, &lt, &ref_type)
+    } else {
+        if all_fields(&ast.data).into_iter().any(is_unpin) {
+            panic!("The #[unpin] attribute can only be used with #[RefAccessors(pin)].");
+        }
+        quote! {}
+    };
+
+    // Neither pinned nor owned projections may be used on types implementing `Drop`.
+    let not_drop = if options.pin || options.owned {
+        impl_not_drop(ast)
+    } else {
+        quote! {}
+    };
+
+    let lens_impl = impl_lenses(ast, &lt, &ref_type);
+
+    let raw_impl = if options.raw {
+        impl_raw_accessors(ast, &ref_type)
+    } else {
+        quote! {}
+    };
 
     let (implgen, typegen, where_clause) = generics.split_for_impl();
+    let (wrap_implgen, _, _) = wrap_generics.split_for_impl();
+    let (_, wrapped_typegen, _) = wrapped_generics.split_for_impl();
+
+    let wrap_impl = quote! {
+        #[allow(non_camel_case_types, non_snake_case)]
+        impl #wrap_implgen ::ref_clone::RefAccessorsMut<#wrap_lt, #ref_path #wrapped_typegen> for Ref<#lt, #name #ref_types, #ref_type> #where_clause {
+            #[inline(always)]
+            fn wrap(&#wrap_lt mut self) -> #ref_path #wrapped_typegen {
+                ::ref_clone::RefAccessors::to_wrapped(self.reborrow())
+            }
+        }
+    };
 
-    match &ast.data {
+    // Shared borrows are wrapped with `__new_unsafe` and unique borrows with `__new_unsafe_mut`,
+    // so that the fields of a Unique ref are never derived from a shared borrow.
+    // Owned fields are left in place, and share the memory of the value with `__own`.
+    let new_shared = quote! { ::ref_clone::Ref::__new_unsafe };
+    let new_unique = quote! { ::ref_clone::Ref::__new_unsafe_mut };
+    let new_owned = quote! { __alloc.__own };
+
+    let (def, gen_shared, gen_unique, gen_owned, gen_cell) = match &ast.data {
         Struct(DataStruct { fields, .. }) => {
-            let (def, gen) = gen(fields, &ref_path, &lt, &ref_type);
+            let (def, gen_shared) = gen(fields, &ref_path, &lt, &ref_type, &new_shared);
+            let (_, gen_unique) = gen(fields, &ref_path, &lt, &ref_type, &new_unique);
+            let (_, gen_owned) = gen(fields, &ref_path, &lt, &ref_type, &new_owned);
+            let gen_cell = gen_cell(fields, &ref_path);
+            // Tuple structs need a semicolon after their definition.
+            let semi = match fields {
+                Unnamed(_) => quote! { ; },
+                _ => quote! {},
+            };
+            (
+                quote! {
+                    #vis struct #ref_path #implgen #def #semi
+                },
+                vec![quote! { #name #gen_shared }],
+                vec![quote! { #name #gen_unique }],
+                vec![quote! { #name #gen_owned }],
+                Some(gen_cell),
+            )
+        }
+        Enum(DataEnum { variants, .. }) => {
+            let variants = variants.iter();
+            let mut def = vec![];
+            let mut gen_shared = vec![];
+            let mut gen_unique = vec![];
+            let mut gen_owned = vec![];
+            for Variant { fields, ident, .. } in variants {
+                let variant_path = quote! {
+                    #ref_path :: #ident
+                };
+                let (variant_def, shared) = gen(fields, &variant_path, &lt, &ref_type, &new_shared);
+                let (_, unique) = gen(fields, &variant_path, &lt, &ref_type, &new_unique);
+                let (_, owned) = gen(fields, &variant_path, &lt, &ref_type, &new_owned);
+                def.push(quote! {
+                    #ident #variant_def ,
+                });
+                gen_shared.push(quote! {
+                    #name :: #ident #shared,
+                });
+                gen_unique.push(quote! {
+                    #name :: #ident #unique,
+                });
+                gen_owned.push(quote! {
+                    #name :: #ident #owned,
+                });
+            }
+            (
+                quote! {
+                    #vis enum #ref_path #implgen {
+                        #(#def)*
+                    }
+                },
+                gen_shared,
+                gen_unique,
+                gen_owned,
+                None,
+            )
+        }
+        _ => {
+            panic!("Can not use RefAccessors with a union.");
+        }
+    };
+
+    // Owned values are split into their fields in place, which would skip the `drop` of types implementing `Drop`.
+    // As such, owned projections must be asked for explicitly, and such types are rejected.
+    let fork = match (gen_cell, options.owned) {
+        (Some(gen_cell), false) => quote! {
+            <#ref_type as ::ref_clone::FieldType>::_fork_fields(
+                self,
+                |x| match x {
+                    #(#gen_shared)*
+                },
+                |x| match x {
+                    #(#gen_unique)*
+                },
+                #gen_cell,
+            )
+        },
+        (Some(gen_cell), true) => quote! {
+            <#ref_type as ::ref_clone::Access>::_fork_any(
+                self,
+                |x| match x {
+                    #(#gen_shared)*
+                },
+                |x| match x {
+                    #(#gen_unique)*
+                },
+                |x| {
+                    let (x, __alloc) = unsafe { x.__into_parts() };
+                    match x {
+                        #(#gen_owned)*
+                    }
+                },
+                #gen_cell,
+            )
+        },
+        (None, true) => quote! {
+            <#ref_type as ::ref_clone::AccessType>::_fork_all(
+                self,
+                |x| match x {
+                    #(#gen_shared)*
+                },
+                |x| match x {
+                    #(#gen_unique)*
+                },
+                |x| {
+                    let (x, __alloc) = unsafe { x.__into_parts() };
+                    match x {
+                        #(#gen_owned)*
+                    }
+                },
+            )
+        },
+        (None, false) => quote! {
+            <#ref_type as ::ref_clone::RefType>::_fork(
+                self,
+                |x| match x {
+                    #(#gen_shared)*
+                },
+                |x| match x {
+                    #(#gen_unique)*
+                },
+            )
+        },
+    };
+
+    let downgrade_impl = impl_downgrade(ast, &ref_path, &generics, &lt, &ref_type);
+
+    quote! {
+        #[allow(non_camel_case_types, non_snake_case)]
+        #def
+        // `__new_unsafe` is deprecated for use outside of the generated code.
+        #[allow(non_camel_case_types, non_snake_case, deprecated)]
+        impl #implgen ::ref_clone::RefAccessors<#ref_path #typegen> for Ref<#lt, #name #ref_types, #ref_type> #where_clause {
+            #[inline(always)]
+            fn to_wrapped(self) -> #ref_path #typegen {
+                #fork
+            }
+        }
+        #wrap_impl
+        #downgrade_impl
+        #lens_impl
+        #pin_impl
+        #raw_impl
+        #not_drop
+    }
+}
+
+/// Generates the `downgrade` method of the wrapper, which downgrades every field with `Ref::downgrade`.
+fn impl_downgrade(
+    ast: &DeriveInput,
+    ref_path: &Ident,
+    generics: &Generics,
+    lt: &Lifetime,
+    ref_type: &Ident,
+) -> proc_macro2::TokenStream {
+    let vis = &ast.vis;
+    let target = format_ident!("__{}__downgrade_type", ast.ident);
+    let target_generics = extend_generics(&ast.generics, quote! { #lt, #target });
+    let (_, target_typegen, _) = target_generics.split_for_impl();
+    let (implgen, typegen, where_clause) = generics.split_for_impl();
+    let arm = |path: proc_macro2::TokenStream, fields: &Fields| match fields {
+        Named(fields) => {
+            let idents = fields
+                .named
+                .iter()
+                .map(|x| x.ident.as_ref().unwrap())
+                .collect::<Vec<_>>();
+            quote! {
+                #path { #(#idents),* } => #path { #(#idents: ::ref_clone::Ref::downgrade(#idents)),* },
+            }
+        }
+        Unnamed(fields) => {
+            let idents = (0..fields.unnamed.len())
+                .map(|i| format_ident!("_{}", i))
+                .collect::<Vec<_>>();
             quote! {
-                #[allow(non_camel_case_types, non_snake_case)]
-                #vis struct #ref_path #implgen #def
-                #[allow(non_camel_case_types, non_snake_case)]
-                impl #implgen ::ref_clone::RefAccessors<#ref_path #typegen> for Ref<#lt, #name #ref_types, #ref_type> #where_clause {
+                #path(#(#idents),*) => #path(#(::ref_clone::Ref::downgrade(#idents)),*),
+            }
+        }
+        _ => panic!(
+            "Panic in function impl_downgrade: Unit types are not supported for RefAccessors."
+        ),
+    };
+    let arms = match &ast.data {
+        Struct(DataStruct { fields, .. }) => vec![arm(quote! { #ref_path }, fields)],
+        Enum(DataEnum { variants, .. }) => variants
+            .iter()
+            .map(|Variant { ident, fields, .. }| arm(quote! { #ref_path :: #ident }, fields))
+            .collect(),
+        _ => panic!("Can not use RefAccessors with a union."),
+    };
+    quote! {
+        #[allow(non_camel_case_types, non_snake_case)]
+        impl #implgen #ref_path #typegen #where_clause {
+            /// Weakens the type of the borrow of every field, such as from `Unique` to `Shared`.
+            #[inline(always)]
+            #vis fn downgrade<#target : ::ref_clone::RefType>(self) -> #ref_path #target_typegen
+            where
+                #ref_type : ::ref_clone::Weaker<#target>,
+            {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    }
+}
+
+/// Generates a `FieldLens` for every field of a struct, created with `lens_` followed by the name of the field.
+/// The function has the visibility of the field, as the lens gives access to it.
+fn impl_lenses(ast: &DeriveInput, lt: &Lifetime, ref_type: &Ident) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    let (implgen, typegen, where_clause) = ast.generics.split_for_impl();
+    let fields = match &ast.data {
+        Struct(DataStruct { fields, .. }) => fields,
+        // The fields of an enum are not always present.
+        _ => return quote! {},
+    };
+    let (functions, impls) = fields
+        .iter()
+        .enumerate()
+        .map(|(i, Field { vis, ident, ty, .. })| {
+            let (member, lens_name) = match ident {
+                Some(ident) => (Member::Named(ident.clone()), format_ident!("lens_{}", ident)),
+                None => (Member::Unnamed(i.into()), format_ident!("lens_{}", i)),
+            };
+            let lens = quote! { ::ref_clone::FieldLens<#name #typegen, #i> };
+            (
+                quote! {
                     #[inline(always)]
-                    fn to_wrapped(self) -> #ref_path #typegen {
-                        match self.as_ref() {
-                            #name #gen
+                    #vis fn #lens_name() -> #lens {
+                        unsafe { ::ref_clone::FieldLens::__new() }
+                    }
+                },
+                quote! {
+                    #[allow(non_camel_case_types, non_snake_case)]
+                    impl #implgen ::ref_clone::RefLens<#name #typegen, #ty> for #lens #where_clause {
+                        #[inline(always)]
+                        fn get<#lt, #ref_type : ::ref_clone::RefType>(&self, x: Ref<#lt, #name #typegen, #ref_type>) -> Ref<#lt, #ty, #ref_type> {
+                            ::ref_clone::Ref::map(x, |x| &x.#member, |x| &mut x.#member)
                         }
                     }
-                }
+                },
+            )
+        })
+        .unzip::<_, _, Vec<_>, Vec<_>>();
+    quote! {
+        #[allow(non_camel_case_types, non_snake_case)]
+        impl #implgen #name #typegen #where_clause {
+            #(#functions)*
+        }
+        #(#impls)*
+    }
+}
+
+/// Generates the closure which wraps each field of a struct behind a Cell.
+/// The fields are projected with `addr_of_mut!`, as the value may not be borrowed.
+fn gen_cell(fields: &Fields, ref_path: &Ident) -> proc_macro2::TokenStream {
+    let projections = fields.iter().enumerate().map(|(i, x)| {
+        let member = match &x.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(i.into()),
+        };
+        quote! {
+            #member: unsafe { ::ref_clone::Ref::__new_unsafe_cell(::std::ptr::addr_of_mut!((*x).#member)) }
+        }
+    });
+    quote! {
+        |x: ::std::ptr::NonNull<_>| {
+            let x = x.as_ptr();
+            #ref_path {
+                #(#projections,)*
+            }
+        }
+    }
+}
+
+/// First TokenStream is the definition of the pinned wrapper (without the outside wrapper).
+/// Second TokenStream converts the unpinned wrapper into the pinned one.
+fn gen_pinned(
+    fields: &Fields,
+    ref_path: &impl ToTokens,
+    pin_path: &impl ToTokens,
+    lt: &Lifetime,
+    ref_type: &Ident,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let interior = fields.iter().map(|x| {
+        let Field { vis, ident, ty, .. } = x;
+        let ident = ident.iter();
+        if is_unpin(x) {
+            quote! {
+                #vis #(#ident :)* Ref<#lt, #ty, #ref_type>,
+            }
+        } else {
+            quote! {
+                #vis #(#ident :)* ::std::pin::Pin<Ref<#lt, #ty, #ref_type>>,
             }
         }
+    });
+    let idents = fields
+        .iter()
+        .enumerate()
+        .map(|(i, x)| x.ident.clone().unwrap_or_else(|| format_ident!("_{}", i)))
+        .collect::<Vec<_>>();
+    let pinned = fields.iter().zip(&idents).map(|(x, ident)| {
+        if is_unpin(x) {
+            quote! { #ident }
+        } else {
+            quote! { ::std::pin::Pin::new_unchecked(#ident) }
+        }
+    });
+    match fields {
+        Named(_) => (
+            quote! {
+                { #(#interior)* }
+            },
+            quote! {
+                #ref_path { #(#idents),* } => #pin_path { #(#idents: #pinned),* }
+            },
+        ),
+        Unnamed(_) => (
+            quote! {
+                ( #(#interior)* )
+            },
+            quote! {
+                #ref_path(#(#idents),*) => #pin_path(#(#pinned),*)
+            },
+        ),
+        _ => panic!("Panic in function gen_pinned: Unit types are not supported for RefAccessors."),
+    }
+}
+
+/// Generates the pinned wrapper, and the impl which makes pinned projections sound:
+/// the type is only `Unpin` if every structurally pinned field is. It is also checked not to implement `Drop`.
+fn impl_pin_accessors(
+    ast: &DeriveInput,
+    ref_path: &Ident,
+    pin_path: &Ident,
+    pin_generics: &Generics,
+    lt: &Lifetime,
+    ref_type: &Ident,
+) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    for attr in &ast.attrs {
+        if attr.path.is_ident("repr") && attr.tokens.to_string().contains("packed") {
+            panic!("RefAccessors(pin) can not be used with #[repr(packed)].");
+        }
+    }
+
+    let (implgen, typegen, where_clause) = pin_generics.split_for_impl();
+    let (_, start_typegen, start_where_clause) = ast.generics.split_for_impl();
+
+    let (def, arms) = match &ast.data {
+        Struct(DataStruct { fields, .. }) => {
+            let (def, arm) = gen_pinned(fields, ref_path, pin_path, lt, ref_type);
+            let semi = match fields {
+                Unnamed(_) => quote! { ; },
+                _ => quote! {},
+            };
+            (
+                quote! {
+                    #vis struct #pin_path #implgen #def #semi
+                },
+                vec![arm],
+            )
+        }
         Enum(DataEnum { variants, .. }) => {
-            let variants = variants.iter();
-            let (def, gen) = variants
-                .map(|x| {
-                    let Variant { fields, ident, .. } = x;
-                    let (def, gen) = gen(
+            let (def, arms) = variants
+                .iter()
+                .map(|Variant { fields, ident, .. }| {
+                    let (def, arm) = gen_pinned(
                         fields,
-                        &quote! {
-                            #ref_path :: #ident
-                        },
-                        &lt,
-                        &ref_type,
+                        &quote! { #ref_path :: #ident },
+                        &quote! { #pin_path :: #ident },
+                        lt,
+                        ref_type,
                     );
-                    (
-                        quote! {
-                            #ident #def ,
-                        },
-                        quote! {
-                            #name :: #ident #gen,
-                        },
-                    )
+                    (quote! { #ident #def , }, quote! { #arm , })
                 })
                 .unzip::<_, _, Vec<_>, Vec<_>>();
-            let def = def.iter();
-            let gen = gen.iter();
-            quote! {
-                #[allow(non_camel_case_types, non_snake_case)]
-                #vis enum #ref_path #implgen {
-                    #(#def)*
-                }
-                #[allow(non_camel_case_types, non_snake_case)]
-                impl #implgen ::ref_clone::RefAccessors<#ref_path #typegen> for Ref<#lt, #name #ref_types, #ref_type> #where_clause {
-                    #[inline(always)]
-                    fn to_wrapped(self) -> #ref_path #typegen {
-                        match self.as_ref() {
-                            #(#gen)*
-                        }
+            (
+                quote! {
+                    #vis enum #pin_path #implgen {
+                        #(#def)*
+                    }
+                },
+                arms,
+            )
+        }
+        _ => panic!("Can not use RefAccessors with a union."),
+    };
+
+    // The extra lifetime keeps the `Unpin` bound from being trivially false, such as for `PhantomPinned` fields.
+    let unpin_path = format_ident!("__{}__Unpin", name);
+    let unpin_generics = {
+        let mut generics = ast.generics.clone();
+        generics.params.insert(0, parse_quote! { '__pin });
+        generics
+    };
+    let (unpin_implgen, unpin_typegen, _) = unpin_generics.split_for_impl();
+    let mut unpin_where_clause = start_where_clause.cloned().unwrap_or_else(|| WhereClause {
+        where_token: Default::default(),
+        predicates: Punctuated::new(),
+    });
+    unpin_where_clause
+        .predicates
+        .push(parse_quote! { #unpin_path #unpin_typegen : ::std::marker::Unpin });
+    let pinned_fields = all_fields(&ast.data)
+        .into_iter()
+        .filter(|x| !is_unpin(x))
+        .enumerate()
+        .map(|(i, Field { ty, .. })| {
+            let ident = format_ident!("__field{}", i);
+            quote! { #ident: #ty }
+        });
+    quote! {
+        #[allow(non_camel_case_types, non_snake_case)]
+        #def
+        #[allow(non_camel_case_types, non_snake_case)]
+        impl #implgen ::ref_clone::RefAccessorsPinned<#pin_path #typegen> for ::std::pin::Pin<Ref<#lt, #name #start_typegen, #ref_type>> #where_clause {
+            #[inline(always)]
+            fn to_wrapped_pinned(self) -> #pin_path #typegen {
+                unsafe {
+                    match ::ref_clone::RefAccessors::to_wrapped(::std::pin::Pin::into_inner_unchecked(self)) {
+                        #(#arms)*
                     }
                 }
             }
         }
-        _ => {
-            panic!("Can not use RefAccessors with a union.");
+        const _: () = {
+            #[allow(non_camel_case_types, non_snake_case, dead_code)]
+            pub struct #unpin_path #unpin_implgen #start_where_clause {
+                __pin: ::std::marker::PhantomData<(&'__pin (), fn() -> #name #start_typegen)>,
+                #(#pinned_fields,)*
+            }
+            impl #unpin_implgen ::std::marker::Unpin for #name #start_typegen #unpin_where_clause {}
+
+        };
+    }
+}
+
+/// Generates a check that the type does not implement `Drop`, as two impls of the same trait then overlap.
+fn impl_not_drop(ast: &DeriveInput) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    let (implgen, typegen, where_clause) = ast.generics.split_for_impl();
+    let drop_path = format_ident!("__{}__MustNotImplDrop", name);
+    quote! {
+        const _: () = {
+            #[allow(non_camel_case_types)]
+            trait #drop_path {}
+            #[allow(drop_bounds)]
+            impl<T: ::std::ops::Drop> #drop_path for T {}
+            impl #implgen #drop_path for #name #typegen #where_clause {}
+        };
+    }
+}
+
+/// Generates the wrapper of `RawRef`s. Fields are projected with `addr_of_mut!`, which never creates a reference.
+fn impl_raw_accessors(ast: &DeriveInput, ref_type: &Ident) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    let raw_path = format_ident!("{}RawRef", name);
+    let raw_generics = extend_generics(
+        &ast.generics,
+        quote! {
+            #ref_type : ::ref_clone::RefType
+        },
+    );
+    let (implgen, typegen, where_clause) = raw_generics.split_for_impl();
+    let start_typegen = ast.generics.split_for_impl().1;
+
+    let fields = match &ast.data {
+        Struct(DataStruct { fields, .. }) => fields,
+        // The discriminant of an enum can not be read without a reference to it.
+        _ => panic!("RefAccessors(raw) can only be used with a struct."),
+    };
+    let interior = fields.iter().map(|Field { vis, ident, ty, .. }| {
+        let ident = ident.iter();
+        quote! {
+            #vis #(#ident :)* ::ref_clone::RawRef<#ty, #ref_type>,
+        }
+    });
+    let projections = fields.iter().enumerate().map(|(i, x)| {
+        let member = match &x.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(i.into()),
+        };
+        quote! {
+            #member: ::ref_clone::RawRef::__new_raw(::std::ptr::addr_of_mut!((*ptr).#member))
+        }
+    });
+    let (def, semi) = match fields {
+        Named(_) => (quote! { { #(#interior)* } }, quote! {}),
+        Unnamed(_) => (quote! { ( #(#interior)* ) }, quote! { ; }),
+        _ => panic!(
+            "Panic in function impl_raw_accessors: Unit types are not supported for RefAccessors."
+        ),
+    };
+
+    quote! {
+        #[allow(non_camel_case_types, non_snake_case)]
+        #vis struct #raw_path #implgen #def #semi
+        #[allow(non_camel_case_types, non_snake_case)]
+        impl #implgen ::ref_clone::RawRefAccessors<#raw_path #typegen> for ::ref_clone::RawRef<#name #start_typegen, #ref_type> #where_clause {
+            #[inline(always)]
+            unsafe fn to_wrapped_raw(self) -> #raw_path #typegen {
+                // Shared RawRefs are stored as `*mut` too, and are never written through.
+                let ptr = self.as_ptr() as *mut #name #start_typegen;
+                #raw_path {
+                    #(#projections,)*
+                }
+            }
         }
     }
 }
@@ -1,7 +1,30 @@
+#![cfg_attr(feature = "nightly", feature(arbitrary_self_types))]
+
+#[cfg(test)]
+mod soundness;
+
 #[cfg(test)]
 mod tests {
-    use ref_clone::*;
     use ref_clone::RefAccessors;
+    use ref_clone::*;
+    use std::cell::Cell;
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::collections::HashMap;
+    use std::collections::LinkedList;
+    use std::collections::VecDeque;
+    use std::future::ready;
+    use std::future::Future;
+    use std::future::Ready;
+    use std::marker::PhantomPinned;
+    use std::pin::pin;
+    use std::pin::Pin;
+    use std::rc::Rc;
+    use std::sync::Mutex;
+    use std::sync::RwLock;
+    use std::task::Context;
+    use std::task::Poll;
+    use std::task::Waker;
 
     #[RefAccessors]
     struct Foo {
@@ -108,4 +131,842 @@ mod tests {
         assert_eq!(iter.next(), Some(Ref::new(&3)));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn test_index() {
+        let mut foo = [1, 2, 3];
+        assert_eq!(Ref::new(&foo).index_ref(1), Ref::new(&2));
+        *<[i32; 3]>::index_ref(Ref::new(&mut foo), 2).as_mut() = 4;
+        assert_eq!(foo, [1, 2, 4]);
+    }
+
+    fn max_ref<S: RefType>(x: Ref<'_, [i32], S>) -> Option<Ref<'_, i32, S>> {
+        if x.len() <= 1 {
+            return Ref::split_first(x).map(|(first, _)| first);
+        }
+        let mid = x.len() / 2;
+        let (a, b) = Ref::split_at(x, mid);
+        match (max_ref(a), max_ref(b)) {
+            (Some(a), Some(b)) if *b > *a => Some(b),
+            (a, b) => a.or(b),
+        }
+    }
+
+    #[test]
+    fn test_split() {
+        let mut foo = [3, 7, 2, 5];
+        *max_ref(Ref::new(&mut foo[..])).unwrap() = 0;
+        assert_eq!(*max_ref(Ref::new(&foo[..])).unwrap(), 5);
+        let (last, rest) = Ref::split_last(Ref::new(&mut foo).into()).unwrap();
+        *last.into_mut() += rest.iter().sum::<i32>();
+        assert_eq!(foo, [3, 0, 2, 10]);
+        assert!(Ref::split_at_checked(Ref::new(&foo).into(), 5).is_none());
+        let (a, b) = Ref::split_at_checked(Ref::new(&foo).into(), 4).unwrap();
+        assert_eq!((a.len(), b.len()), (4, 0));
+        assert!(Ref::split_first(Ref::new(&[0u8; 0]).into()).is_none());
+        assert_eq!(Ref::new(&foo).split_first(), Some((&3, &[0, 2, 10][..])));
+    }
+
+    #[test]
+    fn test_deref() {
+        let mut foo = Box::new(Foo { x: 1, y: vec![] });
+        *get_foo_child(Ref::new(&mut foo).deref_ref()).as_mut() = 2;
+        assert_eq!(foo.x, 2);
+    }
+
+    #[test]
+    fn test_reborrow() {
+        let mut foo = Foo { x: 1, y: vec![] };
+        let mut r = Unique::new(&mut foo);
+        *get_foo_child(r.reborrow()).as_mut() += 1;
+        get_foo_vec_child(r.reborrow()).as_mut().push(2);
+        assert_eq!(*get_foo_child(r.as_shared()), 2);
+        *r.wrap().x.as_mut() += 1;
+        r.wrap().y.as_mut().push(3);
+        assert_eq!(*r.wrap().x, 3);
+        assert_eq!(foo.y, [2, 3]);
+    }
+
+    #[RefAccessors(owned)]
+    struct Pair {
+        first: String,
+        second: Vec<u8>,
+    }
+
+    fn get_pair_first<S: AccessType>(a: Ref<'_, Pair, S>) -> Ref<'_, String, S> {
+        a.to_wrapped().first
+    }
+
+    #[test]
+    fn test_owned() {
+        let mut pair = Pair {
+            first: "a".to_string(),
+            second: vec![1],
+        };
+        assert_eq!(*get_pair_first(Ref::new(&pair)), "a");
+        get_pair_first(Ref::new(&mut pair)).as_mut().push('b');
+        assert_eq!(get_pair_first(Owned::new(pair)).into_inner(), "ab");
+    }
+
+    #[test]
+    fn test_owned_ref_fn() {
+        let f = || RefFn::<_, _, (u8, String), _, _>::new_owned(|x| &x.1, |x| &mut x.1, |x| x.1);
+        let mut x = (1, "a".to_string());
+        f().ap(Ref::new(&mut x)).as_mut().push('b');
+        assert_eq!(*f().ap(Ref::new(&x)), "ab");
+        assert_eq!(f().ap(Owned::new(x)).into_inner(), "ab");
+    }
+
+    #[test]
+    fn test_owned_iter() {
+        let foo = Owned::new([1, 2, 3]);
+        assert_eq!(
+            foo.into_iter().map(|x| x.into_inner()).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        let foo = Owned::from_box(vec!["a".to_string(), "b".to_string()].into_boxed_slice());
+        assert_eq!(foo.index_ref(1).into_inner(), "b");
+        assert_eq!(Owned::new([1, 2, 3]).index_ref(2).into_inner(), 3);
+    }
+
+    #[test]
+    fn test_owned_in_place() {
+        let x = Owned::new(Pair {
+            first: "a".to_string(),
+            second: vec![1],
+        });
+        let pair = &*x as *const Pair as usize;
+        let second = x.to_wrapped().second;
+        assert_eq!(
+            &*second as *const Vec<u8> as usize - pair,
+            std::mem::offset_of!(Pair, second)
+        );
+        let v = vec![1u64, 2, 3];
+        let ptr = v.as_ptr();
+        let refs = Owned::new(v).into_iter().collect::<Vec<_>>();
+        assert_eq!(&*refs[1] as *const u64, ptr.wrapping_add(1));
+        assert_eq!(
+            refs.into_iter().map(|x| *x.into_box()).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+    }
+
+    #[RefAccessors(pin)]
+    struct Counting<F> {
+        future: F,
+        #[unpin]
+        polls: u32,
+    }
+
+    impl<F: Future> Future for Counting<F> {
+        type Output = (F::Output, u32);
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let CountingPinRef { future, mut polls } = Unique::new_pinned(self).to_wrapped_pinned();
+            *polls.as_mut() += 1;
+            Ref::into_pinned_mut(future).poll(cx).map(|x| (x, *polls))
+        }
+    }
+
+    fn get_polls<S: RefType>(x: Pin<Ref<'_, Counting<Ready<u8>>, S>>) -> Ref<'_, u32, S> {
+        x.to_wrapped_pinned().polls
+    }
+
+    fn assert_unpin<T: Unpin>() {}
+
+    #[test]
+    fn test_pinned() {
+        assert_unpin::<Counting<Ready<u8>>>();
+        let mut fut = pin!(Counting {
+            future: ready(3),
+            polls: 0,
+        });
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready((3, 1)));
+        assert_eq!(*get_polls(Shared::new_pinned(fut.as_ref())), 1);
+        *get_polls(Unique::new_pinned(fut.as_mut())).as_mut() = 5;
+        assert_eq!(fut.polls, 5);
+    }
+
+    #[test]
+    fn test_pinned_owned() {
+        let mut x = Owned::new_pinned((PhantomPinned, 1u8));
+        let f = RefFn::<_, _, (PhantomPinned, u8), _>::new(|x| &x.1, |x| &mut x.1);
+        let y = unsafe { f.ap_pinned(Unique::new_pinned(x.as_mut())) };
+        *Ref::into_pinned_mut(y).get_mut() += 1;
+        assert_eq!(x.1, 2);
+    }
+
+    #[RefAccessors]
+    struct Point(u32, u32);
+
+    fn get_point_x<S: FieldType>(x: Ref<'_, Point, S>) -> Ref<'_, u32, S> {
+        x.to_wrapped().0
+    }
+
+    #[test]
+    fn test_cell() {
+        let point = Cell::new(Point(1, 2));
+        let x = get_point_x(CellShared::new(&point));
+        let PointRef(_, y) = CellShared::new(&point).to_wrapped();
+        assert_eq!(x.get(), 1);
+        x.set(y.get());
+        assert_eq!(y.replace(3), 2);
+        assert_eq!(y.take(), 3);
+        let point = point.into_inner();
+        assert_eq!((point.0, point.1), (2, 0));
+        assert_eq!(*get_point_x(Shared::new(&point)), 2);
+    }
+
+    #[test]
+    fn test_cell_slice() {
+        let values = Cell::new([1, 2, 3]);
+        let cells = CellShared::new(&values);
+        cells.index_ref(0).set(cells.index_ref(2).get());
+        for x in cells {
+            x.set(x.get() * 2);
+        }
+        assert_eq!(values.get(), [6, 4, 6]);
+    }
+
+    fn add_locked<S: RefType>(cell: &RefCell<Foo>, lock: &RwLock<Foo>, mutex: &Mutex<Foo>) -> i64 {
+        let mut cell = cell.borrow_kind::<S>();
+        let mut lock = lock.lock_kind::<S>().unwrap();
+        let mut mutex = mutex.lock_kind::<S>().unwrap();
+        *get_foo_child(cell.reborrow())
+            + *get_foo_child(lock.reborrow())
+            + *get_foo_child(mutex.reborrow())
+    }
+
+    #[test]
+    fn test_lock() {
+        let cell = RefCell::new(Foo { x: 1, y: vec![] });
+        let lock = RwLock::new(Foo { x: 2, y: vec![] });
+        let mutex = Mutex::new(Foo { x: 3, y: vec![] });
+        assert_eq!(add_locked::<Shared>(&cell, &lock, &mutex), 6);
+        assert_eq!(add_locked::<Unique>(&cell, &lock, &mutex), 6);
+        let shared = cell.borrow_kind::<Shared>();
+        assert_eq!(cell.borrow_kind::<Shared>().x, shared.x);
+        assert!(cell.try_borrow_mut().is_err());
+        drop(shared);
+        *get_foo_child(lock.lock_kind::<Unique>().unwrap().reborrow()) = 4;
+        assert_eq!(lock.read().unwrap().x, 4);
+        let guard = lock
+            .lock_kind::<Shared>()
+            .unwrap()
+            .into_guard_ref()
+            .into_guard();
+        assert_eq!(guard.x, 4);
+    }
+
+    fn borrow_foo_x<S: RefType>(cell: &RefCell<Foo>) -> GuardRef<'_, LockGuard<'_, Foo>, i64, S> {
+        cell.borrow_kind::<S>()
+            .map(RefFn::new(|x: &Foo| &x.x, |x: &mut Foo| &mut x.x))
+    }
+
+    #[test]
+    fn test_guard() {
+        let cell = RefCell::new(Foo { x: 1, y: vec![2] });
+        *borrow_foo_x::<Unique>(&cell) += 1;
+        assert_eq!(*borrow_foo_x::<Shared>(&cell), 2);
+        let mut guard = GuardRef::<_, _, Unique>::new(cell.borrow_mut());
+        let FooRef { mut x, mut y } = guard.to_wrapped();
+        *x += 1;
+        y.push(3);
+        drop(guard);
+        let guard = GuardRef::new(cell.borrow());
+        assert_eq!(guard.x, 3);
+        assert_eq!(guard.y, [2, 3]);
+    }
+
+    fn owned_foo_x<S: RefType>(foo: Box<Foo>) -> OwnedRef<Box<Foo>, i64, S> {
+        OwnedRef::new(foo).map_ref(|x| x.to_wrapped().x)
+    }
+
+    #[test]
+    fn test_owned_ref() {
+        let mut x = owned_foo_x::<Unique>(Box::new(Foo { x: 1, y: vec![] }));
+        *x += 1;
+        assert_eq!(
+            owned_foo_x::<Shared>(x.into_owner()).as_shared(),
+            Shared::new(&2)
+        );
+        let mut y = OwnedRef::<_, _, Unique>::new(vec![1, 2, 3]).index_ref(1);
+        *y.reborrow() = 4;
+        assert_eq!(y.into_owner(), [1, 4, 3]);
+        let rc = Rc::new(Foo { x: 5, y: vec![6] });
+        let y = OwnedRef::new(rc.clone())
+            .map(RefFn::new(|x: &Foo| &x.y[..], |x: &mut Foo| &mut x.y[..]))
+            .index_ref(0);
+        assert_eq!(*y, 6);
+        assert_eq!(Rc::strong_count(&rc), 2);
+        drop(y);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    fn get_foo_first<S: RefType>(a: Ref<'_, Foo, S>) -> Option<Ref<'_, u32, S>> {
+        TryRefFn::new(|x: &Foo| x.y.first(), |x: &mut Foo| x.y.first_mut()).ap(a)
+    }
+
+    #[derive(Debug)]
+    enum Shape {
+        Circle(f64),
+        Point,
+    }
+
+    fn get_radius<S: RefType>(a: Ref<'_, Shape, S>) -> Result<Ref<'_, f64, S>, &'static str> {
+        TryRefFn::new_result(
+            |x: &Shape| match x {
+                Shape::Circle(r) => Ok(r),
+                _ => Err("not a circle"),
+            },
+            |x: &mut Shape| match x {
+                Shape::Circle(r) => Ok(r),
+                _ => Err("not a circle"),
+            },
+        )
+        .ap(a)
+    }
+
+    #[test]
+    fn test_try_ref_fn() {
+        let mut foo = Foo { x: 1, y: vec![] };
+        assert_eq!(get_foo_first(Shared::new(&foo)), None);
+        foo.y.push(2);
+        *get_foo_first(Unique::new(&mut foo)).unwrap() += 1;
+        assert_eq!(foo.y, [3]);
+        let mut shape = Shape::Circle(1.0);
+        *get_radius(Unique::new(&mut shape)).unwrap() = 2.0;
+        assert_eq!(*get_radius(Shared::new(&shape)).unwrap(), 2.0);
+        assert_eq!(get_radius(Shared::new(&Shape::Point)), Err("not a circle"));
+    }
+
+    #[test]
+    fn test_ref_fn_then() {
+        let mut foo = Foo {
+            x: 1,
+            y: vec![2, 3],
+        };
+        let first = RefFn::new(|x: &Foo| &x.y, |x: &mut Foo| &mut x.y).then(RefFn::new(
+            |x: &Vec<u32>| &x[0],
+            |x: &mut Vec<u32>| &mut x[0],
+        ));
+        *first.ap(Unique::new(&mut foo)) += 1;
+        assert_eq!(foo.y, [3, 3]);
+        assert_eq!(RefFn::identity().ap(Shared::new(&foo)).x, 1);
+        fn second(x: &Foo) -> &u32 {
+            &x.y[1]
+        }
+        fn second_mut(x: &mut Foo) -> &mut u32 {
+            &mut x.y[1]
+        }
+        let fields: Vec<DynRefFn<Foo, u32>> = vec![
+            DynRefFn::new(|x: &Foo| &x.y[0], |x: &mut Foo| &mut x.y[0]),
+            RefFn::new(second, second_mut).boxed(),
+        ];
+        for f in &fields {
+            *f.ap(Unique::new(&mut foo)) *= 2;
+        }
+        assert_eq!(foo.y, [6, 6]);
+        let last = TryRefFn::new(|x: &Foo| x.y.last(), |x: &mut Foo| x.y.last_mut()).and_then(
+            TryRefFn::new(
+                |x: &u32| Some(x).filter(|x| **x > 5),
+                |x: &mut u32| Some(x).filter(|x| **x > 5),
+            ),
+        );
+        assert_eq!(last.ap(Shared::new(&foo)).as_deref(), Some(&6));
+    }
+
+    #[test]
+    fn test_then_borrowed() {
+        let name = String::from("name");
+        let mut pair = (Wrapper(name.as_str(), 1), 2);
+        let count = RefFn::new(
+            |x: &(Wrapper<&str>, u8)| &x.0,
+            |x: &mut (Wrapper<&str>, u8)| &mut x.0,
+        )
+        .then(RefFn::new(
+            |x: &Wrapper<&str>| &x.1,
+            |x: &mut Wrapper<&str>| &mut x.1,
+        ));
+        *count.ap(Unique::new(&mut pair)) += 1;
+        assert_eq!(pair.0 .1, 2);
+        let first = TryRefFn::new(
+            |x: &(Wrapper<&str>, u8)| Some(&x.0),
+            |x: &mut (Wrapper<&str>, u8)| Some(&mut x.0),
+        )
+        .and_then(TryRefFn::new(
+            |x: &Wrapper<&str>| Some(&x.0),
+            |x: &mut Wrapper<&str>| Some(&mut x.0),
+        ));
+        assert_eq!(first.ap(Shared::new(&pair)).as_deref(), Some(&"name"));
+    }
+
+    #[RefAccessors]
+    struct Wrapper<T>(T, u8);
+
+    fn get_with<'a, S: RefType, L: RefLens<Foo, i64>>(
+        lens: &L,
+        a: Ref<'a, Foo, S>,
+    ) -> Ref<'a, i64, S> {
+        lens.get(a)
+    }
+
+    #[test]
+    fn test_lens() {
+        let mut foo = Foo { x: 1, y: vec![2] };
+        *get_with(&Foo::lens_x(), Unique::new(&mut foo)) += 1;
+        assert_eq!(*get_with(&Foo::lens_x(), Shared::new(&foo)), 2);
+        let inner = Wrapper::lens_0().then(Foo::lens_y());
+        inner.get(Unique::new(&mut Wrapper(foo, 3))).push(4);
+        let mut w = Wrapper(Foo { x: 5, y: vec![] }, 6);
+        inner.to_ref_fn().ap(Unique::new(&mut w)).push(7);
+        assert_eq!(*Wrapper::lens_1().to_ref_fn().ap(Shared::new(&w)), 6);
+        let first = DynRefFn::new(|x: &Vec<u32>| &x[0], |x: &mut Vec<u32>| &mut x[0]);
+        assert_eq!(*inner.then(first).get(Shared::new(&w)), 7);
+        fn x(x: &Foo) -> &i64 {
+            &x.x
+        }
+        fn x_mut(x: &mut Foo) -> &mut i64 {
+            &mut x.x
+        }
+        assert_eq!(*get_with(&RefFn::new(x, x_mut), Shared::new(&w.0)), 5);
+    }
+
+    struct Meters(f64);
+
+    impl DerefRef for Meters {
+        type Target = f64;
+        fn deref_ref<'a, S: RefType>(this: Ref<'a, Self, S>) -> Ref<'a, f64, S> {
+            Ref::map(this, |x| &x.0, |x| &mut x.0)
+        }
+    }
+
+    #[test]
+    fn test_map() {
+        let mut m = Meters(1.0);
+        *Ref::new(&mut m).deref_ref() += 1.0;
+        assert_eq!(*Ref::new(&m).deref_ref(), 2.0);
+        let mut shape = Shape::Circle(1.0);
+        fn radius(
+            x: Ref<'_, Shape, Unique>,
+        ) -> Result<Ref<'_, f64, Unique>, Ref<'_, Shape, Unique>> {
+            Ref::filter_map(
+                x,
+                |x| match x {
+                    Shape::Circle(r) => Some(r),
+                    _ => None,
+                },
+                |x| match x {
+                    Shape::Circle(r) => Some(r),
+                    _ => None,
+                },
+            )
+        }
+        *radius(Ref::new(&mut shape)).unwrap() = 3.0;
+        assert_eq!(*get_radius(Ref::new(&shape)).unwrap(), 3.0);
+        let mut point = Shape::Point;
+        let mut original = radius(Ref::new(&mut point)).unwrap_err();
+        *original = Shape::Circle(4.0);
+        assert!(matches!(point, Shape::Circle(r) if r == 4.0));
+        // `map` does not hide the methods of the value, such as `Option::map`.
+        assert_eq!(Ref::new(&Some(1)).map(|x| x + 1), Some(2));
+    }
+
+    struct TreeNode {
+        key: String,
+        value: Vec<u32>,
+        children: [Option<Box<TreeNode>>; 2],
+    }
+
+    fn key_value<S: RefType>(
+        x: Ref<'_, TreeNode, S>,
+    ) -> (Ref<'_, String, S>, Ref<'_, Vec<u32>, S>) {
+        Ref::map_split(x, |x| (&x.key, &x.value), |x| (&mut x.key, &mut x.value))
+    }
+
+    #[test]
+    fn test_map_split() {
+        let mut node = TreeNode {
+            key: "a".to_string(),
+            value: vec![],
+            children: [None, None],
+        };
+        let (key, mut value) = key_value(Ref::new(&mut node));
+        value.push(key.len() as u32);
+        assert_eq!(*key_value(Ref::new(&node)).1, [1]);
+        let (key, value, children) = Ref::map_split_n(
+            Ref::new(&mut node),
+            |x| (&x.key, &x.value, &x.children),
+            |x| (&mut x.key, &mut x.value, &mut x.children),
+        );
+        children.into_mut()[0] = Some(Box::new(TreeNode {
+            key: key.clone(),
+            value: value.clone(),
+            children: [None, None],
+        }));
+        assert_eq!(node.children[0].as_ref().unwrap().key, "a");
+    }
+
+    fn first_ok<S: RefType>(x: Ref<'_, [Result<u32, String>], S>) -> Option<Ref<'_, u32, S>> {
+        x.into_iter().find_map(Ref::ok)
+    }
+
+    #[test]
+    fn test_option() {
+        let mut x = Some(1);
+        for mut y in Ref::new(&mut x) {
+            *y += 1;
+        }
+        assert_eq!(Ref::new(&x).as_option(), Some(Ref::new(&2)));
+        assert_eq!(Owned::new(None::<u8>).as_option(), None);
+        let mut results = vec![Err("a".to_string()), Ok(1)];
+        *first_ok(Ref::new(&mut results[..])).unwrap() += 1;
+        assert_eq!(*first_ok(Ref::new(&results[..])).unwrap(), 2);
+        Ref::err(Ref::new(&mut results[0])).unwrap().push('b');
+        assert_eq!(
+            Owned::new(results.remove(0))
+                .as_result()
+                .unwrap_err()
+                .into_inner(),
+            "ab"
+        );
+        let mut y = Some(Ok::<_, String>(3));
+        *Ref::transpose(Ref::new(&mut y)).unwrap().unwrap() += 1;
+        assert_eq!(y, Some(Ok(4)));
+        // `ok` does not hide `Result::ok` for Copy values.
+        assert_eq!(Ref::new(&Ok::<u8, u8>(1)).ok(), Some(1));
+    }
+
+    fn values<S: RefType>(x: Ref<'_, [(String, u32)], S>) -> impl Iterator<Item = Ref<'_, u32, S>> {
+        x.into_iter().map(Ref::split_tuple).map(|(_, value)| value)
+    }
+
+    #[test]
+    fn test_tuple() {
+        let mut entries = vec![("a".to_string(), 1), ("b".to_string(), 2)];
+        for mut value in values(Ref::new(&mut entries[..])) {
+            *value *= 10;
+        }
+        assert_eq!(
+            values(Ref::new(&entries[..]))
+                .map(|x| *x)
+                .collect::<Vec<_>>(),
+            [10, 20]
+        );
+        let (a, b, c) = Ref::new(&(1u8, 'b', "c")).split_tuple();
+        assert_eq!((*a, *b, *c), (1, 'b', "c"));
+        let (name, _) = Owned::new(entries.remove(0)).split_tuple();
+        assert_eq!(name.into_inner(), "a");
+        let mut point = (1, 2, 3);
+        for mut x in Ref::new(&mut point) {
+            *x += 1;
+        }
+        assert_eq!(point, (2, 3, 4));
+    }
+
+    fn sum<'a, C: IntoIteratorRef<'a, Item = u32> + ?Sized>(x: &'a C) -> u32 {
+        Ref::new(x).into_iter().map(|x| *x).sum()
+    }
+
+    fn double<'a, C: IntoIteratorRef<'a, Item = u32> + ?Sized>(x: &'a mut C) {
+        for mut x in Ref::new(x) {
+            *x *= 2;
+        }
+    }
+
+    #[test]
+    fn test_collections() {
+        let mut v = vec![1, 2];
+        let mut d = VecDeque::from(vec![3, 4]);
+        let mut b = vec![5, 6].into_boxed_slice();
+        let mut l = LinkedList::from([7, 8]);
+        double(&mut v);
+        double(&mut d);
+        double(&mut b);
+        double(&mut l);
+        assert_eq!((sum(&v), sum(&d), sum(&b), sum(&l)), (6, 14, 22, 30));
+        *Ref::new(&mut v).index_ref(0) += 1;
+        *Ref::new(&mut d).index_ref(1) += 1;
+        *Ref::new(&mut b).index_ref(0) += 1;
+        assert_eq!((v[0], d[1], b[0]), (3, 9, 11));
+        assert_eq!(Owned::new(d).index_ref(1).into_inner(), 9);
+        assert_eq!(Ref::new(&l).into_iter().next_back(), Some(Ref::new(&16)));
+        assert_eq!(v.iter().len(), 2);
+        assert_eq!(Ref::new(&mut b).into_iter().len(), b.len());
+        assert_eq!(
+            Owned::new(l).into_iter().next_back().map(Ref::into_inner),
+            Some(16)
+        );
+        let mut s = "hello world".to_string();
+        Ref::new(&mut s).index_ref(..5).make_ascii_uppercase();
+        assert_eq!(&*Ref::new(&s).index_ref(6..), "world");
+        assert_eq!(&*Ref::new("abc").index_ref(1..=1), "b");
+        let bytes = Ref::as_bytes(Ref::new("abc"));
+        assert_eq!(*bytes.index_ref(1), b'b');
+        assert_eq!(bytes.into_iter().map(|x| *x).collect::<Vec<_>>(), b"abc");
+        assert_eq!(&*Owned::new(s).index_ref(..=4).into_box(), "HELLO");
+    }
+
+    fn score<'a, S: RefType>(
+        scores: Ref<'a, HashMap<String, u32>, S>,
+        name: &str,
+    ) -> Option<Ref<'a, u32, S>> {
+        scores.get_ref(name)
+    }
+
+    #[test]
+    fn test_maps() {
+        let mut scores = HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+        *score(Ref::new(&mut scores), "a").unwrap() += 10;
+        assert_eq!(score(Ref::new(&scores), "a"), Some(Ref::new(&11)));
+        assert!(score(Ref::new(&scores), "c").is_none());
+        *Ref::new(&mut scores).index_ref("b") += 1;
+        for (name, mut value) in Ref::new(&mut scores) {
+            *value += name.len() as u32;
+        }
+        let mut values = Ref::new(&scores)
+            .values_ref()
+            .map(|x| *x)
+            .collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, [4, 12]);
+        assert_eq!(Ref::new(&mut scores).into_iter().len(), 2);
+        let mut owned = Owned::new(scores.clone())
+            .into_iter()
+            .map(|(k, v)| (k, v.into_inner()))
+            .collect::<Vec<_>>();
+        owned.sort();
+        assert_eq!(owned, [("a".to_string(), 12), ("b".to_string(), 4)]);
+        assert_eq!(
+            Owned::new(scores.clone())
+                .values_ref()
+                .map(|x| x.into_inner())
+                .sum::<u32>(),
+            16
+        );
+        assert_eq!(Owned::new(scores).index_ref("a").into_inner(), 12);
+
+        let mut tree = BTreeMap::from([(1, 'a'), (2, 'b'), (3, 'c')]);
+        for (_, mut c) in Ref::new(&mut tree).range_ref(2..) {
+            c.make_ascii_uppercase();
+        }
+        assert_eq!(
+            Ref::new(&tree).values_ref().map(|x| *x).collect::<String>(),
+            "aBC"
+        );
+        assert_eq!(Ref::new(&tree).into_iter().map(|(k, _)| *k).sum::<i32>(), 6);
+        assert_eq!(*Ref::new(&tree).index_ref(&1), 'a');
+        assert_eq!(Ref::new(&tree).values_ref().len(), 3);
+        assert_eq!(
+            Owned::new(tree.clone())
+                .values_ref()
+                .map(|x| x.into_inner())
+                .collect::<String>(),
+            "aBC"
+        );
+        assert_eq!(
+            Owned::new(tree)
+                .into_iter()
+                .map(|(k, _)| k)
+                .collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+    }
+
+    fn shout<S: RefType>(x: Ref<String, S>) -> Ref<str, S> {
+        x.deref_ref()
+    }
+
+    #[test]
+    fn test_deref_pointers() {
+        let mut s = String::from("hi");
+        shout(Ref::new(&mut s)).make_ascii_uppercase();
+        assert_eq!(&*shout(Ref::new(&s)), "HI");
+
+        let mut v = vec![1, 2];
+        Ref::new(&mut v).deref_ref().reverse();
+        assert_eq!(Ref::new(&v).deref_ref().first(), Some(&2));
+
+        let mut path = std::path::PathBuf::from("a/b");
+        assert_eq!(
+            Ref::new(&mut path).deref_ref().file_name(),
+            Some("b".as_ref())
+        );
+
+        let borrowed = [1, 2];
+        let mut cow = std::borrow::Cow::Borrowed(&borrowed[..]);
+        Ref::new(&mut cow).deref_ref()[0] = 3;
+        assert_eq!((&*cow, borrowed), (&[3, 2][..], [1, 2]));
+
+        let mut rc = Rc::new(1);
+        *Ref::new(&mut rc).deref_ref() += 1;
+        let other = rc.clone();
+        assert_eq!(*Ref::new(&rc).deref_ref(), 2);
+        assert!(Ref::new(&mut rc).try_deref_ref().is_err());
+        drop(other);
+        assert!(Ref::new(&mut rc).try_deref_ref().is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Arc is shared")]
+    fn test_deref_shared_arc() {
+        let mut arc = std::sync::Arc::new(1);
+        let _other = arc.clone();
+        Ref::new(&mut arc).deref_ref();
+    }
+
+    fn len_of<S: Weaker<Shared>>(x: Ref<Vec<u32>, S>) -> usize {
+        let x: Ref<Vec<u32>, Shared> = x.downgrade();
+        x.len()
+    }
+
+    fn first_of(x: PointRef<Shared>) -> u32 {
+        *x.0
+    }
+
+    #[test]
+    fn test_downgrade() {
+        let mut foo = Foo {
+            x: 1,
+            y: vec![2, 3],
+        };
+        assert_eq!(len_of(Ref::new(&mut foo.y)), 2);
+        assert_eq!(len_of(Ref::new(&foo.y)), 2);
+        let shared: Ref<Foo, Shared> = Ref::new(&mut foo).into();
+        assert_eq!(*get_foo_child(shared), 1);
+        let unique = Ref::new(&mut foo).to_wrapped();
+        let mut wrapped: FooRef<Unique> = unique.downgrade();
+        *wrapped.x += 1;
+        assert_eq!(foo.x, 2);
+
+        let mut point = Point(4, 5);
+        assert_eq!(first_of(Ref::new(&mut point).to_wrapped().downgrade()), 4);
+        let mut value = Enum::OtherVariant { y: 6 };
+        let value = Ref::new(&mut value).to_wrapped().downgrade::<Shared>();
+        assert!(matches!(value, EnumRef::OtherVariant { y } if *y == 6));
+        assert_eq!(*Ref::new(&mut 7).into_shared(), 7);
+    }
+
+    fn clear_if_unique<S: RefType>(x: Ref<Vec<u32>, S>) -> usize {
+        match x.try_into_unique() {
+            Ok(mut x) => {
+                x.clear();
+                0
+            }
+            Err(x) => x.len(),
+        }
+    }
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(Shared::KIND, RefKind::Shared);
+        assert_eq!(<Unique as RefType>::KIND, RefKind::Unique);
+        let mut v = vec![1, 2];
+        assert_eq!(clear_if_unique(Ref::new(&v)), 2);
+        assert_eq!(clear_if_unique(Ref::new(&mut v)), 0);
+        assert!(v.is_empty());
+        let mut x = 1;
+        match Ref::new(&mut x).into_either() {
+            Either::Left(_) => unreachable!(),
+            Either::Right(x) => *x += 1,
+        }
+        assert_eq!(Ref::new(&x).into_either(), Either::Left(&2));
+    }
+
+    fn native_iter<S: RefType>(
+        x: Ref<Vec<u32>, S>,
+    ) -> S::Select<std::slice::Iter<u32>, std::slice::IterMut<u32>> {
+        Ref::select(x, |x| <[u32]>::iter(x), |x| <[u32]>::iter_mut(x))
+    }
+
+    fn native_first<S: RefType>(x: Ref<'_, [u32], S>) -> S::Native<'_, u32> {
+        Ref::map(x, |x| &x[0], |x| &mut x[0]).into_native()
+    }
+
+    #[test]
+    fn test_native() {
+        let mut v = vec![1, 2];
+        for x in native_iter::<Unique>(Ref::new(&mut v)) {
+            *x += 1;
+        }
+        assert_eq!(native_iter::<Shared>(Ref::new(&v)).sum::<u32>(), 5);
+        *native_first::<Unique>(Ref::new(&mut v[..])) = 0;
+        assert_eq!(native_first::<Shared>(Ref::new(&v[..])), &0);
+        let x = Ref::<Vec<u32>, Unique>::from_native(&mut v);
+        assert_eq!(x.into_native(), &mut vec![0, 3]);
+    }
+
+    fn largest<'a>(x: Ref<'a, [u32], Shared>) -> Ref<'a, u32, Shared> {
+        let mut max = Ref::map(x, |x| &x[0], |x| &mut x[0]);
+        for y in x {
+            if y > max {
+                max = y;
+            }
+        }
+        max
+    }
+
+    #[test]
+    fn test_std_traits() {
+        let mut v = [3, 5, 4];
+        let shared = Ref::new(&v[..]);
+        let copy = shared;
+        assert_eq!(*largest(shared), 5);
+        assert!(Ref::ptr_eq(&shared, &copy.clone()));
+        assert_eq!(format!("{:p}", shared), format!("{:p}", &v[..]));
+
+        let mut r = Ref::new(&mut v[0]);
+        r += 2;
+        r *= 3;
+        r <<= 1;
+        assert_eq!(r, &30);
+        assert_eq!(r, Ref::new(&30));
+        let set = std::collections::HashSet::from([Ref::new(&1), Ref::new(&2)]);
+        assert!(set.contains(&2));
+        assert_eq!(AsRef::<u32>::as_ref(&Ref::new(&7)), &7);
+
+        let mut iter = vec![1, 2, 3].into_iter();
+        let mut r = Ref::new(&mut iter);
+        assert_eq!(r.as_mut().next(), Some(1));
+        assert_eq!(r.into_mut().sum::<u32>(), 5);
+    }
+
+    #[RefAccessors(raw)]
+    #[repr(C)]
+    struct Node {
+        next: *mut Node,
+        value: u32,
+    }
+
+    fn get_node_value<S: RefType>(x: RawRef<Node, S>) -> RawRef<u32, S> {
+        unsafe { x.to_wrapped_raw().value }
+    }
+
+    #[test]
+    fn test_raw() {
+        let mut node = Node {
+            next: std::ptr::null_mut(),
+            value: 3,
+        };
+        let value = get_node_value(Unique::new_raw(&mut node));
+        unsafe { *value.as_mut_ptr() = 4 };
+        let value = get_node_value(Shared::new_raw(&node));
+        assert_eq!(unsafe { *value.to_ref() }, 4);
+        let next = unsafe { Unique::new(&mut node).into_raw().to_wrapped_raw().next };
+        assert!(unsafe { next.to_ref() }.is_null());
+    }
+
+    #[cfg(feature = "nightly")]
+    impl Foo {
+        fn x_receiver<'a, S: RefType>(self: Ref<'a, Self, S>) -> Ref<'a, i64, S> {
+            self.to_wrapped().x
+        }
+    }
+
+    #[cfg(feature = "nightly")]
+    #[test]
+    fn test_receiver() {
+        let foo = Foo { x: 4, y: vec![] };
+        assert_eq!(Ref::new(&foo).x_receiver(), Ref::new(&4));
+    }
 }
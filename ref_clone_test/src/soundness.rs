@@ -0,0 +1,445 @@
+//! Tests of the unsafe and aliasing-sensitive paths, such as Unique projections, owned values, guards and the
+//! raw and pinned wrappers, so that aliasing bugs show up under Miri. Other behaviour is tested in the crate root.
+//!
+//! Run with `cargo +nightly miri test -p ref_clone_test`.
+
+use ref_clone::*;
+use std::rc::Rc;
+
+#[RefAccessors]
+struct Named {
+    a: u8,
+    b: Vec<u8>,
+}
+
+#[RefAccessors]
+enum Either {
+    Left { a: u8, b: u8 },
+    Right(u16),
+}
+
+#[RefAccessors(owned)]
+enum Counted {
+    Two(Rc<()>, Rc<()>),
+    One { a: Rc<()> },
+}
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+#[test]
+fn auto_traits() {
+    assert_send::<Ref<'_, u8, Shared>>();
+    assert_sync::<Ref<'_, u8, Shared>>();
+    assert_send::<Ref<'_, u8, Unique>>();
+    assert_sync::<Ref<'_, u8, Unique>>();
+    assert_send::<Ref<'_, std::cell::Cell<u8>, Unique>>();
+    assert_send::<Ref<'_, std::cell::Cell<u8>, Owned>>();
+    assert_sync::<Ref<'_, u8, Owned>>();
+}
+
+#[test]
+fn lifetime_covariance() {
+    fn shorten<'a, 'b: 'a>(x: Ref<'b, u8, Unique>) -> Ref<'a, u8, Unique> {
+        x
+    }
+    let mut x = 1;
+    *shorten(Unique::new(&mut x)).as_mut() = 2;
+    assert_eq!(x, 2);
+}
+
+#[test]
+fn derive_named() {
+    let mut x = Named { a: 1, b: vec![2] };
+    let NamedRef { mut a, mut b } = Unique::new(&mut x).to_wrapped();
+    *a.as_mut() += 1;
+    b.as_mut().push(3);
+    *a.as_mut() += 1;
+    assert_eq!(x.a, 3);
+    assert_eq!(x.b, [2, 3]);
+    let NamedRef { a, b } = Shared::new(&x).to_wrapped();
+    assert_eq!(*a, 3);
+    assert_eq!(*b, [2, 3]);
+}
+
+#[test]
+fn derive_wrap() {
+    let mut x = Named { a: 1, b: vec![] };
+    let mut r = Unique::new(&mut x);
+    let mut a = r.wrap().a;
+    *a.as_mut() += 1;
+    r.wrap().b.as_mut().push(1);
+    let NamedRef { mut a, .. } = r.wrap();
+    *a.as_mut() += 1;
+    *r.as_mut() = Named { a: 0, b: vec![] };
+    *r.wrap().a.as_mut() += 1;
+    assert_eq!(x.a, 1);
+    assert!(x.b.is_empty());
+}
+
+#[test]
+fn reborrow() {
+    let mut x = [1, 2];
+    let mut r = Unique::new(&mut x);
+    *r.reborrow().index_ref(0).as_mut() = 3;
+    assert_eq!(*r.as_shared().index_ref(0), 3);
+    *r.reborrow().index_ref(1).as_mut() = 4;
+    let r = r.into_mut();
+    r[0] = 5;
+    assert_eq!(x, [5, 4]);
+}
+
+#[test]
+fn derive_enum() {
+    let mut x = Either::Left { a: 1, b: 2 };
+    match Unique::new(&mut x).to_wrapped() {
+        EitherRef::Left { mut a, mut b } => {
+            std::mem::swap(a.as_mut(), b.as_mut());
+        }
+        EitherRef::Right(_) => unreachable!(),
+    }
+    match Shared::new(&x).to_wrapped() {
+        EitherRef::Left { a, b } => assert_eq!((*a, *b), (2, 1)),
+        EitherRef::Right(_) => unreachable!(),
+    }
+    let mut x = Either::Right(1);
+    if let EitherRef::Right(mut a) = Unique::new(&mut x).to_wrapped() {
+        *a.as_mut() = 5;
+    }
+    assert!(matches!(x, Either::Right(5)));
+}
+
+#[test]
+fn into_iter_unique() {
+    let mut x = [1, 2, 3];
+    let mut refs = Unique::new(&mut x).into_iter().collect::<Vec<_>>();
+    for r in refs.iter_mut().rev() {
+        *r.as_mut() *= 2;
+    }
+    assert_eq!(x, [2, 4, 6]);
+    let mut x = vec![1, 2, 3];
+    for mut r in Unique::new(&mut x[..]) {
+        *r.as_mut() += 1;
+    }
+    assert_eq!(x, [2, 3, 4]);
+}
+
+#[test]
+fn owned_drop() {
+    let rc = Rc::new(());
+    let x = Owned::new(Counted::Two(rc.clone(), rc.clone()));
+    assert_eq!(Rc::strong_count(&rc), 3);
+    match x.to_wrapped() {
+        CountedRef::Two(a, _) => {
+            assert_eq!(Rc::strong_count(&rc), 3);
+            drop(a.into_inner());
+            assert_eq!(Rc::strong_count(&rc), 2);
+        }
+        CountedRef::One { .. } => unreachable!(),
+    }
+    assert_eq!(Rc::strong_count(&rc), 1);
+    let x = Owned::new(Counted::One { a: rc.clone() });
+    if let CountedRef::One { a } = x.as_shared().to_wrapped() {
+        assert_eq!(Rc::strong_count(&a), 2);
+    }
+    drop(x);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+#[test]
+fn owned_iter_drop() {
+    let rc = Rc::new(());
+    let x = Owned::new([rc.clone(), rc.clone(), rc.clone()]);
+    let mut iter = x.into_iter();
+    let first = iter.next().unwrap();
+    assert_eq!(Rc::strong_count(&rc), 4);
+    drop(iter);
+    assert_eq!(Rc::strong_count(&rc), 2);
+    drop(first);
+    let x = Owned::from_box(vec![rc.clone(), rc.clone()].into_boxed_slice());
+    let second = x.index_ref(1);
+    assert_eq!(Rc::strong_count(&rc), 2);
+    drop(second);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+#[RefAccessors(pin)]
+struct SelfRef {
+    value: u8,
+    ptr: *const u8,
+    _pinned: std::marker::PhantomPinned,
+    #[unpin]
+    count: u8,
+}
+
+#[test]
+fn pinned_projection() {
+    let x = std::pin::pin!(SelfRef {
+        value: 1,
+        ptr: std::ptr::null(),
+        _pinned: std::marker::PhantomPinned,
+        count: 0,
+    });
+    let mut x = Unique::new_pinned(x);
+    let SelfRefPinRef {
+        value,
+        ptr,
+        mut count,
+        ..
+    } = Ref::reborrow_pinned(&mut x).to_wrapped_pinned();
+    let value = Ref::into_pinned_ref(value);
+    unsafe { *Ref::into_pinned_mut(ptr).get_unchecked_mut() = &*value };
+    *count.as_mut() += 1;
+    let SelfRefPinRef { ptr, count, .. } = Shared::new_pinned(x.as_ref()).to_wrapped_pinned();
+    assert_eq!(unsafe { **ptr }, 1);
+    assert_eq!(*count, 1);
+}
+
+#[RefAccessors(raw)]
+struct Uninit(u8, Box<u32>);
+
+#[test]
+fn raw_projection() {
+    // The fields are written one by one, without ever creating a reference to the uninitialised value.
+    let mut x = std::mem::MaybeUninit::<Uninit>::uninit();
+    let UninitRawRef(a, b) = unsafe { Unique::new_raw(x.as_mut_ptr()).to_wrapped_raw() };
+    unsafe {
+        a.as_mut_ptr().write(1);
+        b.as_mut_ptr().write(Box::new(2));
+    }
+    let mut x = unsafe { x.assume_init() };
+    let y = Unique::new(&mut x).into_raw();
+    let UninitRawRef(a, b) = unsafe { y.to_wrapped_raw() };
+    unsafe {
+        **b.to_ref() += u32::from(*a.to_shared().to_ref());
+    }
+    assert_eq!(*x.1, 3);
+}
+
+#[test]
+fn cell_projection() {
+    let mut x = Named { a: 1, b: vec![1] };
+    let x = Unique::new(&mut x).into_cell_shared();
+    let NamedRef { a, b } = x.to_wrapped();
+    let NamedRef { a: a2, .. } = x.to_wrapped();
+    // Overlapping cell Refs may be used in any order.
+    a.set(2);
+    assert_eq!(a2.replace(3), 2);
+    b.set(vec![2]);
+    assert_eq!(a.get(), 3);
+    assert_eq!(b.take(), [2]);
+    a.swap(&a2);
+    let x = x.into_cell().replace(Named { a: 4, b: vec![] });
+    assert_eq!(x.a, 3);
+}
+
+#[test]
+fn lock_kind() {
+    use std::cell::RefCell;
+    use std::sync::Mutex;
+    use std::sync::RwLock;
+
+    let cell = RefCell::new(Named { a: 1, b: vec![] });
+    let mut x = cell.borrow_kind::<Unique>();
+    let NamedRef { mut a, mut b } = x.reborrow().to_wrapped();
+    *a += 1;
+    b.push(1);
+    assert!(cell.try_borrow().is_err());
+    drop(x);
+    assert_eq!(cell.borrow().a, 2);
+
+    let lock = RwLock::new(1u8);
+    let x = lock.lock_kind::<Shared>().unwrap();
+    let y = lock.lock_kind::<Shared>().unwrap();
+    assert_eq!(*x.as_shared(), *y);
+    assert!(lock.try_write().is_err());
+    drop((x, y));
+
+    // A poisoned lock still gives access to its value.
+    let mutex = Mutex::new(1u8);
+    let _ = std::panic::catch_unwind(|| {
+        let _x = mutex.lock_kind::<Shared>();
+        panic!();
+    });
+    let mut x = mutex.lock_kind::<Unique>().unwrap_err().into_inner();
+    *x.reborrow() += 1;
+    assert_eq!(*x, 2);
+}
+
+#[test]
+fn guard_ref() {
+    use std::cell::RefCell;
+    use std::sync::Mutex;
+
+    let cell = RefCell::new(Named { a: 1, b: vec![] });
+    let x = GuardRef::<_, _, Unique>::new(cell.borrow_mut())
+        .map(RefFn::new(|x: &Named| &x.b, |x: &mut Named| &mut x.b));
+    // Moving the GuardRef keeps the projection valid.
+    let mut x = Box::new(x);
+    x.push(1);
+    x.reborrow().push(2);
+    assert!(cell.try_borrow().is_err());
+    let guard = x.into_guard();
+    assert_eq!(guard.b, [1, 2]);
+    drop(guard);
+    assert_eq!(cell.borrow().b, [1, 2]);
+
+    let mutex = Mutex::new(Named { a: 1, b: vec![] });
+    let mut x = GuardRef::<_, _, Shared>::new(mutex.lock().unwrap());
+    let NamedRef { a, .. } = x.wrap();
+    assert_eq!(*a, 1);
+    assert!(mutex.try_lock().is_err());
+}
+
+#[test]
+fn owned_ref() {
+    let x = OwnedRef::<_, _, Unique>::new(Box::new(Named { a: 1, b: vec![2] }));
+    // Moving the OwnedRef and its Box keeps the projection valid.
+    let mut x = Box::new(x.map_ref(|x| x.to_wrapped().b));
+    x.push(3);
+    let mut z = OwnedRef::<_, _, Shared>::new(Box::new(Named { a: 4, b: vec![] }));
+    let NamedRef { a, .. } = z.wrap();
+    assert_eq!(*a, 4);
+    let mut y = OwnedRef::<_, _, Unique>::new(String::from("ab"));
+    y.make_ascii_uppercase();
+    assert_eq!(&*y, "AB");
+    assert_eq!(x.into_owner().b, [2, 3]);
+}
+
+#[test]
+fn slice_split() {
+    let mut x = [1u8, 2, 3, 4];
+    let (a, b) = Ref::split_at(Unique::new(&mut x).into(), 2);
+    let (mut c, d) = Ref::split_first(b).unwrap();
+    let (mut e, _) = Ref::split_last(d).unwrap();
+    let (mut f, _) = Ref::split_at_checked(a, 1).unwrap();
+    // Every part may be used, as they are disjoint.
+    f.as_mut()[0] += 10;
+    *c += 10;
+    *e += 10;
+    assert_eq!(x, [11, 2, 13, 14]);
+}
+
+#[test]
+fn map_and_filter_map() {
+    let mut x = (1u8, Some(2u8));
+    let mut y = Unique::new(&mut x);
+    *Ref::map(y.reborrow(), |x| &x.0, |x| &mut x.0) += 1;
+    let z = Ref::map(y, |x| &x.1, |x| &mut x.1);
+    // Giving back the original Ref after a failed projection keeps it usable.
+    let z = Ref::filter_map(z, |_| None::<&u8>, |_| None).unwrap_err();
+    let mut w = Ref::filter_map(z, |x| x.as_ref(), |x| x.as_mut()).unwrap();
+    *w += 1;
+    assert_eq!(x, (2, Some(3)));
+}
+
+#[test]
+fn map_split() {
+    let mut x = (1u8, 2u16, [3u8, 4]);
+    let (a, b) = Ref::map_split(
+        Unique::new(&mut x),
+        |x| (&x.0, &x.2),
+        |x| (&mut x.0, &mut x.2),
+    );
+    let (mut c, mut d) = Ref::map_split(b, |x| x.split_at(1), |x| x.split_at_mut(1));
+    *a.into_mut() += 1;
+    c[0] += 1;
+    d[0] += 1;
+    let (e, f, g) = Ref::map_split_n(
+        Unique::new(&mut x),
+        |x| (&x.0, &x.1, &x.2),
+        |x| (&mut x.0, &mut x.1, &mut x.2),
+    );
+    *e.into_mut() += 1;
+    *f.into_mut() += 1;
+    g.into_mut()[0] += 1;
+    assert_eq!(x, (3, 3, [5, 5]));
+}
+
+#[test]
+fn tuple_split() {
+    let mut x = (1u8, vec![2u8], (3u8, 4u8));
+    let (a, mut b, c) = Unique::new(&mut x).split_tuple();
+    let (mut d, mut e) = c.split_tuple();
+    b.push(*a.as_ref());
+    *d += 1;
+    *e += 1;
+    *a.into_mut() += 1;
+    for mut y in Unique::new(&mut x.2) {
+        *y += 1;
+    }
+    assert_eq!(x, (2, vec![2, 1], (5, 6)));
+    let (f, g) = Owned::new((Box::new(1u8), vec![2u8])).split_tuple();
+    assert_eq!((*f.into_inner(), g.into_inner()), (1, vec![2]));
+}
+
+#[test]
+fn collections() {
+    let mut x = (
+        vec![1u8, 2],
+        std::collections::VecDeque::from(vec![3u8]),
+        String::from("ab"),
+    );
+    let (v, d, s) = Unique::new(&mut x).split_tuple();
+    let mut refs: Vec<_> = v.into_iter().chain(d).collect();
+    let last = refs.pop().unwrap();
+    for mut r in refs {
+        *r += *last;
+    }
+    s.index_ref(1..).make_ascii_uppercase();
+    assert_eq!(
+        x,
+        (
+            vec![4, 5],
+            std::collections::VecDeque::from(vec![3]),
+            String::from("aB")
+        )
+    );
+}
+
+#[test]
+fn map_collections() {
+    let mut x = std::collections::BTreeMap::from([(1u8, vec![1u8]), (2, vec![2])]);
+    let mut refs: Vec<_> = Unique::new(&mut x).values_ref().collect();
+    let first = refs.remove(0);
+    refs[0].push(first[0]);
+    let mut y = Unique::new(&mut x);
+    y.reborrow().index_ref(&1).push(3);
+    for (k, mut v) in y.reborrow().range_ref(..=1) {
+        v.push(*k);
+    }
+    y.get_ref(&2).unwrap().push(4);
+    assert_eq!(x[&1], [1, 3, 1]);
+    assert_eq!(x[&2], [2, 1, 4]);
+}
+
+#[test]
+fn owned_split_drop() {
+    let rc = Rc::new(());
+    let x = Owned::new(vec![rc.clone(), rc.clone(), rc.clone()]);
+    let second = x.index_ref(1);
+    assert_eq!(Rc::strong_count(&rc), 2);
+    assert_eq!(Rc::strong_count(&second.into_box()), 2);
+    assert_eq!(Rc::strong_count(&rc), 1);
+    let x = Owned::new(std::collections::VecDeque::from([rc.clone(), rc.clone()]));
+    let first = x.index_ref(0);
+    assert_eq!(Rc::strong_count(&rc), 2);
+    drop(first);
+    let (a, b) = Owned::new((rc.clone(), Some(rc.clone()))).split_tuple();
+    drop(a);
+    let b = b.as_option().unwrap();
+    assert_eq!(Rc::strong_count(&rc), 2);
+    drop(b);
+    let x = Owned::new(std::collections::HashMap::from([
+        (1, rc.clone()),
+        (2, rc.clone()),
+    ]));
+    let mut iter = x.into_iter();
+    let (_, value) = iter.next().unwrap();
+    drop(iter);
+    assert_eq!(Rc::strong_count(&rc), 2);
+    drop(value);
+    assert_eq!(Rc::strong_count(&rc), 1);
+    let units = Owned::new(vec![(); 3]).into_iter().map(Ref::into_inner);
+    assert_eq!(units.count(), 3);
+}